@@ -0,0 +1,168 @@
+//! Short effects on the board: the hint of a guess appearing peg by peg,
+//! the solution revealed peg by peg when the game ends, and a flash on a
+//! win
+
+use crate::Hint;
+use std::time::{Duration, Instant};
+
+/// Time between the pegs appearing
+const PEG_INTERVAL: Duration = Duration::from_millis(120);
+
+/// Time between the flash turning on and off
+const FLASH_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Number of times the flash turns on
+const FLASHES: u32 = 3;
+
+/// Effects in progress. Each effect is kept as the time it started, and
+/// drawing asks how far it has got at the moment.
+#[derive(Debug)]
+pub struct Animations {
+    enabled: bool,
+    /// Row whose hint is appearing
+    hint: Option<(usize, Instant)>,
+    solution: Option<Instant>,
+    flash: Option<Instant>,
+}
+
+impl Default for Animations {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl Animations {
+    /// Animations which show everything at once unless `enabled`
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            hint: None,
+            solution: None,
+            flash: None,
+        }
+    }
+
+    /// Starts the hint of the row appearing
+    pub fn on_guess(&mut self, row: usize, now: Instant) {
+        if self.enabled {
+            self.hint = Some((row, now));
+        }
+    }
+
+    /// Starts revealing the solution, and flashing if the game is won
+    pub fn on_game_over(&mut self, won: bool, now: Instant) {
+        if self.enabled {
+            self.solution = Some(now);
+            if won {
+                self.flash = Some(now);
+            }
+        }
+    }
+
+    /// Part of the hint of the row shown at the moment, with the bulls
+    /// appearing before the cows
+    pub fn hint(&self, row: usize, hint: &Hint, now: Instant) -> Hint {
+        let shown = match self.hint {
+            Some((hint_row, started_at)) if hint_row == row => pegs_shown(started_at, now),
+            _ => return hint.clone(),
+        };
+        let bulls = hint.bulls.min(shown);
+        Hint {
+            bulls,
+            cows: hint.cows.min(shown - bulls),
+        }
+    }
+
+    /// Number of pegs of the solution shown at the moment, out of `holes`
+    pub fn solution_pegs(&self, holes: usize, now: Instant) -> usize {
+        match self.solution {
+            Some(started_at) => pegs_shown(started_at, now).min(holes),
+            None => holes,
+        }
+    }
+
+    /// Whether the flash is on at the moment
+    pub fn is_flashing(&self, now: Instant) -> bool {
+        match self.flash {
+            Some(started_at) => {
+                // each flash is on for a frame, then off for one
+                let frame = frames(started_at, now);
+                let (flash, off) = (frame / 2, frame % 2);
+                flash < FLASHES && off == 0
+            }
+            None => false,
+        }
+    }
+
+    /// Time until the next frame, while any effect is in progress. Effects
+    /// are given up to `holes` pegs.
+    pub fn next_frame(&self, holes: usize, now: Instant) -> Option<Duration> {
+        let pegs_end = |started_at: Instant| started_at + PEG_INTERVAL * holes as u32;
+        let mut ends = self
+            .hint
+            .map(|(_, started_at)| pegs_end(started_at))
+            .into_iter()
+            .chain(self.solution.map(pegs_end))
+            .chain(
+                self.flash
+                    .map(|started_at| started_at + FLASH_INTERVAL * FLASHES * 2),
+            );
+        ends.any(|end| end > now)
+            .then(|| PEG_INTERVAL.min(FLASH_INTERVAL))
+    }
+
+    /// Shows everything at once, e.g. for a new game
+    pub fn clear(&mut self) {
+        self.hint = None;
+        self.solution = None;
+        self.flash = None;
+    }
+}
+
+fn pegs_shown(started_at: Instant, now: Instant) -> usize {
+    (now.duration_since(started_at).as_millis() / PEG_INTERVAL.as_millis()) as usize + 1
+}
+
+fn frames(started_at: Instant, now: Instant) -> u32 {
+    (now.duration_since(started_at).as_millis() / FLASH_INTERVAL.as_millis()) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_pegs() {
+        let start = Instant::now();
+        let mut animations = Animations::default();
+        animations.on_guess(2, start);
+        let hint = Hint { bulls: 1, cows: 2 };
+
+        assert_eq!(animations.hint(2, &hint, start), Hint { bulls: 1, cows: 0 });
+        let later = start + PEG_INTERVAL * 2;
+        assert_eq!(animations.hint(2, &hint, later), hint);
+        assert_eq!(animations.hint(1, &hint, start), hint);
+        assert!(animations.next_frame(4, later).is_some());
+        assert_eq!(animations.next_frame(4, start + PEG_INTERVAL * 4), None);
+
+        animations.on_game_over(true, start);
+        assert_eq!(animations.solution_pegs(4, start + PEG_INTERVAL), 2);
+        assert!(animations.is_flashing(start));
+        assert!(!animations.is_flashing(start + FLASH_INTERVAL));
+        assert!(!animations.is_flashing(start + FLASH_INTERVAL * FLASHES * 2));
+    }
+
+    #[test]
+    fn disabled() {
+        let start = Instant::now();
+        let mut animations = Animations::new(false);
+        animations.on_guess(0, start);
+        animations.on_game_over(true, start);
+
+        let hint = Hint { bulls: 1, cows: 2 };
+        assert_eq!(animations.hint(0, &hint, start), hint);
+        assert_eq!(animations.solution_pegs(4, start), 4);
+        assert!(!animations.is_flashing(start));
+        assert_eq!(animations.next_frame(4, start), None);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::solver::{self, Suggestion};
+use crate::{Guess, Hint, Settings};
+use crossbeam_channel::{Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+/// Number of suggestions listed in the panel
+pub const NUM_SUGGESTIONS: usize = 5;
+
+/// Result of analyzing a position
+pub struct Analysis {
+    /// Number of guesses made when the analysis was requested
+    pub num_guesses: usize,
+    pub suggestions: Vec<Suggestion>,
+    /// Most likely color of each hole and its probability
+    pub likely_colors: Vec<(usize, f64)>,
+}
+
+/// Ranks next guesses in a background thread so that the UI stays
+/// responsive while the solver is running
+pub struct Assistant {
+    tx: Sender<Analysis>,
+    rx: Receiver<Analysis>,
+    analysis: Option<Analysis>,
+    /// `None` if there are too many codes to analyze
+    codes: Option<Arc<Vec<Guess>>>,
+}
+
+impl Assistant {
+    pub fn new(settings: &Settings) -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let codes = solver::code_count(settings).map(|_| Arc::new(solver::all_codes(settings)));
+
+        Self {
+            tx,
+            rx,
+            analysis: None,
+            codes,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.codes.is_some()
+    }
+
+    pub fn receiver(&self) -> &Receiver<Analysis> {
+        &self.rx
+    }
+
+    /// Starts analyzing the position after `guesses`
+    pub fn refresh(&mut self, settings: &Settings, guesses: &[Guess], hints: &[Hint]) {
+        let codes = match &self.codes {
+            Some(codes) => codes.clone(),
+            None => return,
+        };
+
+        let tx = self.tx.clone();
+        let settings = settings.clone();
+        let guesses = guesses.to_vec();
+        let hints = hints.to_vec();
+        thread::spawn(move || {
+            let candidates = solver::candidates(&codes, &guesses, &hints, settings.code_colors());
+            let suggestions = solver::suggest(&settings, &codes, &candidates, NUM_SUGGESTIONS);
+            let likely_colors = solver::likely_colors(&candidates, &settings);
+            let _ = tx.send(Analysis {
+                num_guesses: guesses.len(),
+                suggestions,
+                likely_colors,
+            });
+        });
+    }
+
+    /// Accepts the result unless the position has changed since the request
+    pub fn on_analysis(&mut self, analysis: Analysis, num_guesses: usize) {
+        if analysis.num_guesses == num_guesses {
+            self.analysis = Some(analysis);
+        }
+    }
+
+    /// Latest analysis of the position after `num_guesses` guesses
+    pub fn analysis(&self, num_guesses: usize) -> Option<&Analysis> {
+        self.analysis
+            .as_ref()
+            .filter(|analysis| analysis.num_guesses == num_guesses)
+    }
+}
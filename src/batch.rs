@@ -0,0 +1,167 @@
+use crate::events::{self, Command, GameEvent};
+use crate::hook::Summary;
+use crate::{Game, Guess, State};
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+/// Plays the game without the TUI. Each line of `input` is a guess such as
+/// "1 3 2 4", and each line of `output` is the hint "bulls cows" for it or
+/// "error: <reason>" when the guess was not accepted. With `json`, both are
+/// JSON lines instead.
+pub fn play(
+    game: &mut Game,
+    input: impl BufRead,
+    mut output: impl Write,
+    json: bool,
+) -> Result<()> {
+    if json {
+        let event = GameEvent::NewGame {
+            settings: &game.settings,
+            daily: game.daily,
+        };
+        events::write(&mut output, &event)?;
+    }
+
+    for line in input.lines() {
+        if game.status() != State::Playing {
+            break;
+        }
+
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let guess = if json {
+            serde_json::from_str(&line)
+                .map_err(|err| format!("Invalid command: {}", err))
+                .and_then(|Command::Guess { guess }| to_guess(game, &guess))
+        } else {
+            parse_guess(game, &line)
+        };
+        let result = guess.and_then(|guess| {
+            game.current_guess = guess;
+            let result = game.submit();
+            game.current_guess = Guess::default();
+            result
+        });
+
+        match (result, json) {
+            (Ok(hint), false) => writeln!(output, "{} {}", hint.bulls, hint.cows)?,
+            (Err(message), false) => writeln!(output, "error: {}", message)?,
+            (Ok(hint), true) => {
+                let event = GameEvent::guess(&game.settings, game.guesses.last().unwrap(), &hint);
+                events::write(&mut output, &event)?;
+            }
+            (Err(message), true) => events::write(&mut output, &GameEvent::Error { message })?,
+        }
+        output.flush()?;
+    }
+
+    if json && game.status() != State::Playing {
+        events::write(&mut output, &GameEvent::GameOver(Summary::new(game)))?;
+    }
+
+    Ok(())
+}
+
+/// Colors are separated by spaces or written one digit each
+pub fn parse_guess(game: &Game, line: &str) -> Result<Guess, String> {
+    let numbers = line
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c.to_digit(10) {
+            Some(digit) => Ok(digit as usize),
+            None => Err(format!(
+                "There is no color {}; choose from {}",
+                c,
+                game.settings.color_range()
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    to_guess(game, &numbers)
+}
+
+/// Checks colors numbered as they are shown, e.g. from 1
+fn to_guess(game: &Game, numbers: &[usize]) -> Result<Guess, String> {
+    let holes = game.settings.holes.get();
+
+    let mut guess = Vec::new();
+    for &number in numbers {
+        let color = game.settings.number_color(number).ok_or_else(|| {
+            format!(
+                "There is no color {}; choose from {}",
+                number,
+                game.settings.color_range()
+            )
+        })?;
+        if game.settings.no_duplicate && guess.contains(&color) {
+            return Err(format!("Color {} cannot duplicate", number));
+        }
+        guess.push(color);
+    }
+
+    if guess.len() != holes {
+        return Err(format!("Expected {} colors, got {}", holes, guess.len()));
+    }
+
+    Ok(Guess(guess))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Settings;
+
+    fn play_lines(game: &mut Game, input: &str, json: bool) -> Vec<String> {
+        let mut output = Vec::new();
+        play(game, input.as_bytes(), &mut output, json).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn hints_and_errors() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.solution = Guess(vec![0, 1, 2, 3]);
+
+        let output = play_lines(
+            &mut game,
+            "1 3 2 4\n\n1 2\n7 1 1 1\n1235\n1234\n1111\n",
+            false,
+        );
+        assert_eq!(
+            output,
+            [
+                "2 2",
+                "error: Expected 4 colors, got 2",
+                "error: There is no color 7; choose from 1-6",
+                "3 0",
+                "4 0",
+            ]
+        );
+        assert!(game.status() == State::Won);
+        assert_eq!(game.guesses.len(), 3);
+    }
+
+    #[test]
+    fn json_commands() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.solution = Guess(vec![0, 1, 2, 3]);
+
+        let input = r#"{"command": "guess", "guess": [1, 2, 3, 4]}"#;
+        let output = play_lines(&mut game, &format!("1 2 3 4\n{}\n", input), true);
+        assert_eq!(output.len(), 4);
+        assert!(output[0].starts_with(r#"{"event":"new-game","#));
+        assert!(output[1].starts_with(r#"{"event":"error","#));
+        assert_eq!(
+            output[2],
+            r#"{"event":"guess","guess":[1,2,3,4],"bulls":4,"cows":0}"#
+        );
+        assert!(output[3].starts_with(r#"{"event":"game-over","won":true,"#));
+    }
+}
@@ -0,0 +1,224 @@
+//! Measures how well the solving strategies break codes
+
+use crate::{calc_hint, solver, Guess, Hint, Settings};
+use anyhow::Result;
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Names of the strategies, for the help of the command line
+pub static STRATEGIES: &[&str] = &["knuth", "entropy", "random"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// Minimizes the candidates left in the worst case
+    Knuth,
+    /// Maximizes the expected information gained
+    Entropy,
+    /// Picks a random candidate
+    Random,
+}
+
+impl FromStr for Strategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "knuth" => Ok(Self::Knuth),
+            "entropy" => Ok(Self::Entropy),
+            "random" => Ok(Self::Random),
+            _ => Err(anyhow::anyhow!(
+                "unknown strategy \"{}\"; choose from {}",
+                s,
+                STRATEGIES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Knuth => "knuth",
+            Self::Entropy => "entropy",
+            Self::Random => "random",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Results of breaking a set of codes
+#[derive(Debug)]
+pub struct Report {
+    pub strategy: Strategy,
+    /// Number of guesses taken to break each code
+    pub guesses: Vec<usize>,
+    /// Maximum number of guesses allowed by the rules
+    pub max_guesses: usize,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    pub fn average(&self) -> f64 {
+        self.guesses.iter().sum::<usize>() as f64 / self.guesses.len().max(1) as f64
+    }
+
+    pub fn worst(&self) -> usize {
+        self.guesses.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Share of the codes broken within the guesses allowed by the rules
+    pub fn win_rate(&self) -> f64 {
+        let wins = self
+            .guesses
+            .iter()
+            .filter(|&&guesses| guesses <= self.max_guesses)
+            .count();
+        wins as f64 / self.guesses.len().max(1) as f64
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let count = self.guesses.len();
+        writeln!(f, "Strategy  {}", self.strategy)?;
+        writeln!(f, "Codes     {}", count)?;
+        writeln!(f, "Average   {:.3} guesses", self.average())?;
+        writeln!(f, "Worst     {} guesses", self.worst())?;
+        writeln!(
+            f,
+            "Won       {:.1}% within {} guesses",
+            self.win_rate() * 100.0,
+            self.max_guesses
+        )?;
+        write!(
+            f,
+            "Time      {:.2}s ({:.2}ms per code)",
+            self.elapsed.as_secs_f64(),
+            self.elapsed.as_secs_f64() * 1000.0 / count.max(1) as f64
+        )
+    }
+}
+
+/// Breaks every code, or `sample` random codes drawn with `seed`
+pub fn run(
+    settings: &Settings,
+    strategy: Strategy,
+    sample: Option<usize>,
+    seed: u64,
+) -> Result<Report> {
+    if solver::code_count(settings).is_none() {
+        return Err(anyhow::anyhow!("there are too many codes to benchmark"));
+    }
+
+    let start = Instant::now();
+    let codes = solver::all_codes(settings);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let solutions = match sample {
+        Some(sample) => (0..sample)
+            .map(|_| codes.choose(&mut rng).unwrap().clone())
+            .collect(),
+        None => codes.clone(),
+    };
+
+    let mut solver = Solver {
+        settings,
+        codes: &codes,
+        strategy,
+        rng,
+        cache: HashMap::new(),
+    };
+    let guesses = solutions
+        .iter()
+        .map(|solution| solver.solve(solution))
+        .collect();
+
+    Ok(Report {
+        strategy,
+        guesses,
+        max_guesses: settings.guesses.get(),
+        elapsed: start.elapsed(),
+    })
+}
+
+struct Solver<'a> {
+    settings: &'a Settings,
+    codes: &'a [Guess],
+    strategy: Strategy,
+    rng: StdRng,
+    /// Guesses of the deterministic strategies after each sequence of hints.
+    /// Games against different codes share the first guesses, so most of
+    /// them are looked up here.
+    cache: HashMap<Vec<Hint>, Guess>,
+}
+
+impl Solver<'_> {
+    /// Number of guesses taken to break the code
+    fn solve(&mut self, solution: &Guess) -> usize {
+        let num_colors = self.settings.code_colors();
+        let mut candidates = self.codes.to_vec();
+        let mut hints = Vec::new();
+
+        loop {
+            let guess = self.next_guess(&candidates, &hints);
+            let hint = calc_hint(&guess, solution, num_colors);
+            if hint.bulls == self.settings.holes.get() {
+                return hints.len() + 1;
+            }
+
+            candidates.retain(|code| calc_hint(&guess, code, num_colors) == hint);
+            hints.push(hint);
+        }
+    }
+
+    fn next_guess(&mut self, candidates: &[Guess], hints: &[Hint]) -> Guess {
+        if let Some(guess) = self.cache.get(hints) {
+            return guess.clone();
+        }
+
+        let guess = match self.strategy {
+            Strategy::Knuth => solver::minimax(self.settings, self.codes, candidates),
+            Strategy::Entropy => solver::suggest(self.settings, self.codes, candidates, 1)
+                .pop()
+                .map(|suggestion| suggestion.guess),
+            Strategy::Random => candidates.choose(&mut self.rng).cloned(),
+        }
+        // the solution is always among the candidates
+        .unwrap();
+
+        if self.strategy != Strategy::Random {
+            self.cache.insert(hints.to_vec(), guess.clone());
+        }
+        guess
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn break_every_code() {
+        let settings = Settings {
+            colors: NonZeroUsize::new(4).unwrap(),
+            holes: NonZeroUsize::new(3).unwrap(),
+            ..Default::default()
+        };
+
+        for &name in STRATEGIES {
+            let strategy = name.parse().unwrap();
+            let report = run(&settings, strategy, None, 0).unwrap();
+            assert_eq!(report.guesses.len(), 64);
+            assert!(report.average() >= 1.0);
+            assert!(report.worst() <= 64);
+        }
+
+        let knuth = run(&settings, Strategy::Knuth, None, 0).unwrap();
+        let random = run(&settings, Strategy::Random, Some(10), 0).unwrap();
+        assert_eq!(random.guesses.len(), 10);
+        assert!(knuth.worst() <= 5);
+    }
+}
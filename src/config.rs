@@ -0,0 +1,351 @@
+use crate::hook::{self, Hook};
+use crate::input::InputMode;
+use crate::keymap::KeyMap;
+use crate::macros::Macros;
+use crate::theme::ThemeConfig;
+use crate::Settings;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+static CONFIG_FILE_NAME: &str = "config.toml";
+
+static TEMPLATE: &str = r##"# Configuration of codebreaker
+# Options given on the command line take precedence over this file.
+
+# Number of colors
+# colors = 6
+
+# Maximum number of guesses
+# guesses = 8
+
+# Number of holes per row
+# holes = 4
+
+# Forbid colors to duplicate
+# no-duplicate = false
+
+# Reject guesses which contradict the hints so far, also known as strict
+# hard = false
+
+# Lose the game when the time (in seconds) runs out
+# time-limit = 300
+
+# Fill the rest of the row at random and make the guess when a turn takes
+# longer than this many seconds
+# turn-time = 30
+
+# Lose the turn instead of filling the row when the turn time runs out
+# forfeit-turn = false
+
+# Break a number of distinct digits 0-9 instead of colors
+# digits = false
+
+# Show only how many colors of each guess are correct, not where
+# blind = false
+
+# Hide the hints until all guesses but the last are made
+# static = false
+
+# Let holes of the code be blank, typed as 0
+# allow-empty = false
+
+# Name of the player on the leaderboard
+# name = "anonymous"
+
+# Command to run when a game finishes. It receives the result as JSON on stdin.
+# on-game-over = "cat >> ~/codebreaker.log"
+
+# Seconds to wait for the command before killing it
+# hook-timeout = 10
+
+# Show the progress in the terminal title
+# title = true
+
+# Draw the board with ASCII characters only, for terminals without Unicode
+# ascii = false
+
+# How colors are typed besides the number keys: numbers only, initials (the
+# letters under the legend) or names, completed with tab. Letters bound to
+# actions or macros keep working, and a name starting with one is typed
+# with Shift.
+# input = "numbers"
+
+# Suggest a harder or easier configuration after enough games
+# suggest-difficulty = true
+
+# Color theme: default, bright, light, or one defined below
+# theme = "default"
+
+# Keys of the actions, separated by spaces: characters, "ctrl+" followed by
+# a character, or enter, space, backspace, delete, insert, esc, tab, pageup,
+# pagedown, home, end, up, down, left or right. colors lists the keys
+# selecting the colors in order instead of the number keys. Ctrl+C always
+# quits.
+# [keys]
+# submit = "enter space"
+# undo = "backspace"
+# delete = "delete"
+# cursor-left = "left"
+# cursor-right = "right"
+# insert = "insert"
+# quit = "q esc"
+# save = "ctrl+s"
+# pause = "p"
+# suspend = "ctrl+z"
+# legend = "l"
+# overlay = "o"
+# scroll-up = "pageup"
+# scroll-down = "pagedown"
+# give-up = "g"
+# new-game = "n"
+# help = "?"
+# analyze = "a"
+# share = "s"
+# colors = "123456789"
+
+# Keys which fill the current row with a guess: colors (e.g. "1122"),
+# or "distinct" for as many different colors as there are holes
+# [macros]
+# a = "1122"
+# d = "distinct"
+
+# User-defined theme. Colors are names (e.g. "red", "light-blue"),
+# hex codes (e.g. "#ff8800"), or indices of the 256-color palette.
+# Styles are colors with optional modifiers (e.g. "bold yellow").
+# [themes.mine]
+# base = "default"
+# pegs = ["blue", "red", "green", "yellow", "magenta", "white", "cyan"]
+# bull = "red"
+# cow = "white"
+# empty = "dark-gray"
+# text = "bold"
+"##;
+
+/// Defaults which override the built-in ones
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    colors: Option<NonZeroUsize>,
+    guesses: Option<NonZeroUsize>,
+    holes: Option<NonZeroUsize>,
+    no_duplicate: Option<bool>,
+    #[serde(alias = "strict")]
+    hard: Option<bool>,
+    time_limit: Option<NonZeroU64>,
+    turn_time: Option<NonZeroU64>,
+    forfeit_turn: Option<bool>,
+    digits: Option<bool>,
+    blind: Option<bool>,
+    #[serde(rename = "static")]
+    static_hints: Option<bool>,
+    allow_empty: Option<bool>,
+    name: Option<String>,
+    on_game_over: Option<String>,
+    hook_timeout: Option<u64>,
+    title: Option<bool>,
+    ascii: Option<bool>,
+    input: Option<InputMode>,
+    suggest_difficulty: Option<bool>,
+    theme: Option<String>,
+    keys: BTreeMap<String, String>,
+    macros: BTreeMap<String, String>,
+    themes: BTreeMap<String, ThemeConfig>,
+}
+
+impl Config {
+    /// Name of the player on the leaderboard
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Name of the default theme
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// Command to run when a game finishes
+    pub fn hook(&self) -> Option<Hook> {
+        let timeout = self
+            .hook_timeout
+            .map(Duration::from_secs)
+            .unwrap_or(hook::DEFAULT_TIMEOUT);
+        let command = self.on_game_over.clone()?;
+        Some(Hook::new(command, timeout))
+    }
+
+    /// Whether to show the progress in the terminal title
+    pub fn title(&self) -> Option<bool> {
+        self.title
+    }
+
+    /// Whether to draw the board with ASCII characters only
+    pub fn ascii(&self) -> Option<bool> {
+        self.ascii
+    }
+
+    /// How colors are typed besides the number keys
+    pub fn input(&self) -> Option<InputMode> {
+        self.input
+    }
+
+    /// Whether to suggest another configuration based on the statistics
+    pub fn suggest_difficulty(&self) -> Option<bool> {
+        self.suggest_difficulty
+    }
+
+    /// Keys bound to the actions and colors
+    pub fn keys(&self) -> Result<KeyMap> {
+        KeyMap::parse(&self.keys).context("invalid [keys]")
+    }
+
+    /// Keys bound to the actions and colors, where `overrides` of a rule
+    /// set take precedence over the `[keys]` table
+    pub fn keys_with(&self, overrides: &BTreeMap<String, String>) -> Result<KeyMap> {
+        let mut definitions = self.keys.clone();
+        definitions.extend(overrides.clone());
+        KeyMap::parse(&definitions)
+    }
+
+    /// Entries of the `[keys]` table as they are written
+    pub fn key_definitions(&self) -> &BTreeMap<String, String> {
+        &self.keys
+    }
+
+    /// Keys which fill the current row with a guess
+    pub fn macros(&self, keys: &KeyMap) -> Result<Macros> {
+        Macros::parse(&self.macros, keys)
+    }
+
+    pub fn themes(&self) -> &BTreeMap<String, ThemeConfig> {
+        &self.themes
+    }
+
+    /// Overrides the settings not given on the command line
+    pub fn apply(&self, settings: &mut Settings, matches: &clap::ArgMatches) {
+        let given = |name| matches.occurrences_of(name) > 0;
+
+        if let (false, Some(colors)) = (given("colors"), self.colors) {
+            settings.colors = colors;
+        }
+        if let (false, Some(guesses)) = (given("guesses"), self.guesses) {
+            settings.guesses = guesses;
+        }
+        if let (false, Some(holes)) = (given("holes"), self.holes) {
+            settings.holes = holes;
+        }
+        if let (false, Some(no_duplicate)) = (given("no-duplicate"), self.no_duplicate) {
+            settings.no_duplicate = no_duplicate;
+        }
+        if let (false, Some(hard)) = (given("hard"), self.hard) {
+            settings.hard = hard;
+        }
+        if let (false, Some(time_limit)) = (given("time-limit"), self.time_limit) {
+            settings.time_limit = Some(time_limit);
+        }
+        if let (false, Some(turn_time)) = (given("turn-time"), self.turn_time) {
+            settings.turn_time = Some(turn_time);
+        }
+        // losing turns means nothing without the turn time
+        let timed = settings.turn_time.is_some();
+        if let (false, true, Some(forfeit_turn)) = (given("forfeit-turn"), timed, self.forfeit_turn)
+        {
+            settings.forfeit_turn = forfeit_turn;
+        }
+        if let (false, Some(blind)) = (given("blind"), self.blind) {
+            settings.blind = blind;
+        }
+        if let (false, Some(static_hints)) = (given("static"), self.static_hints) {
+            settings.static_hints = static_hints;
+        }
+        // a blank given on the command line is for a game of colors, and
+        // one from the config is ignored for a game of digits
+        let digits_given = given("digits");
+        if let (false, false, Some(allow_empty)) =
+            (given("allow-empty"), digits_given, self.allow_empty)
+        {
+            settings.allow_empty = allow_empty;
+        }
+        // colors given on the command line mean a game of colors
+        let colors_given = given("colors") || given("no-duplicate") || given("allow-empty");
+        if let (false, false, Some(digits)) = (digits_given, colors_given, self.digits) {
+            settings.digits = digits;
+        }
+    }
+}
+
+pub fn path() -> Result<PathBuf> {
+    let dir = dirs::config_dir().ok_or_else(|| anyhow::anyhow!("config directory not found"))?;
+    Ok(dir.join(env!("CARGO_PKG_NAME")).join(CONFIG_FILE_NAME))
+}
+
+pub fn load() -> Result<Config> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let config =
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))?;
+
+    Ok(config)
+}
+
+/// Writes a commented template to the config file
+pub fn init() -> Result<PathBuf> {
+    let path = path()?;
+    if path.exists() {
+        return Err(anyhow::anyhow!("{} already exists", path.display()));
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, TEMPLATE).with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+    use structopt::StructOpt;
+
+    #[test]
+    fn template_is_valid() {
+        let config: Config = toml::from_str(TEMPLATE).unwrap();
+        assert!(config.colors.is_none());
+
+        let uncommented: String = TEMPLATE
+            .lines()
+            .map(|line| line.strip_prefix("# ").unwrap_or(line))
+            .filter(|line| line.contains(" = ") || line.starts_with('['))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let config: Config = toml::from_str(&uncommented).unwrap();
+        assert_eq!(config.colors, NonZeroUsize::new(6));
+        assert_eq!(config.no_duplicate, Some(false));
+        let keys = config.keys().unwrap();
+        assert!(config.macros(&keys).unwrap().get('d').is_some());
+        assert!(Theme::resolve("mine", config.themes()).is_ok());
+    }
+
+    #[test]
+    fn command_line_takes_precedence() {
+        let config: Config = toml::from_str("colors = 7\nholes = 5\nhard = true").unwrap();
+        let matches = Settings::clap().get_matches_from(["codebreaker", "--holes", "3"]);
+        let mut settings = Settings::from_clap(&matches);
+        config.apply(&mut settings, &matches);
+
+        assert_eq!(settings.colors.get(), 7);
+        assert_eq!(settings.holes.get(), 3);
+        assert!(settings.hard);
+    }
+}
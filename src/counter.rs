@@ -0,0 +1,90 @@
+use crate::solver;
+use crate::{Guess, Hint, Settings};
+
+/// Counts the codes consistent with the hints so far. The codes are
+/// enumerated on the first guess and narrowed down by each new guess only.
+#[derive(Debug, Default)]
+pub struct CandidateCounter {
+    /// `None` until the first guess is made
+    candidates: Option<Vec<Guess>>,
+    /// Number of guesses the candidates are narrowed down by
+    num_guesses: usize,
+}
+
+impl CandidateCounter {
+    /// Narrows down the candidates by the guesses not seen yet
+    pub fn update(&mut self, settings: &Settings, guesses: &[Guess], hints: &[Hint]) {
+        if guesses.len() < self.num_guesses {
+            *self = Self::default();
+        }
+        if guesses.len() == self.num_guesses || solver::code_count(settings).is_none() {
+            return;
+        }
+
+        let candidates = self
+            .candidates
+            .get_or_insert_with(|| solver::all_codes(settings));
+        let num_colors = settings.code_colors();
+        let new_guesses = &guesses[self.num_guesses..];
+        let new_hints = &hints[self.num_guesses..];
+        candidates.retain(|code| solver::is_consistent(code, new_guesses, new_hints, num_colors));
+        self.num_guesses = guesses.len();
+    }
+
+    /// `None` when there are too many codes to enumerate
+    pub fn count(&self, settings: &Settings) -> Option<usize> {
+        match &self.candidates {
+            Some(candidates) => Some(candidates.len()),
+            None => solver::code_count(settings),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_hint;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn narrows_down_incrementally() {
+        let settings = Settings::default();
+        let solution = Guess(vec![0, 1, 2, 3]);
+        let guesses = vec![Guess(vec![0, 0, 1, 1]), Guess(vec![2, 2, 3, 3])];
+        let hints: Vec<_> = guesses
+            .iter()
+            .map(|guess| calc_hint(guess, &solution, 6))
+            .collect();
+
+        let mut counter = CandidateCounter::default();
+        assert_eq!(counter.count(&settings), Some(1296));
+
+        counter.update(&settings, &guesses[..1], &hints[..1]);
+        let after_one = counter.count(&settings).unwrap();
+        counter.update(&settings, &guesses, &hints);
+        let after_two = counter.count(&settings).unwrap();
+
+        let codes = solver::all_codes(&settings);
+        assert_eq!(
+            after_one,
+            solver::candidates(&codes, &guesses[..1], &hints[..1], 6).len()
+        );
+        assert_eq!(
+            after_two,
+            solver::candidates(&codes, &guesses, &hints, 6).len()
+        );
+        assert!(after_two < after_one);
+    }
+
+    #[test]
+    fn too_many_codes() {
+        let settings = Settings {
+            colors: NonZeroUsize::new(9).unwrap(),
+            holes: NonZeroUsize::new(9).unwrap(),
+            ..Default::default()
+        };
+        let mut counter = CandidateCounter::default();
+        counter.update(&settings, &[Guess(vec![0; 9])], &[Hint::default()]);
+        assert_eq!(counter.count(&settings), None);
+    }
+}
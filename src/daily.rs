@@ -0,0 +1,206 @@
+use crate::{data_path, Game, Guess, Settings};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static DAILY_FILE_NAME: &str = "daily.json";
+
+/// Day of the first daily puzzle, counted from the Unix epoch (2020-07-01)
+const FIRST_DAY: u64 = 18444;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Today in UTC, counted from the Unix epoch
+pub fn today() -> u64 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    elapsed.as_secs() / SECONDS_PER_DAY
+}
+
+/// Number of the puzzle shown to players, starting from 1
+pub fn puzzle_number(day: u64) -> u64 {
+    day.saturating_sub(FIRST_DAY) + 1
+}
+
+/// Seed which depends only on the day and the settings so that everyone
+/// playing the daily puzzle gets the same code.
+///
+/// This doesn't rely on `std::hash` because its output is not guaranteed to
+/// be stable across Rust versions.
+pub fn seed(day: u64, settings: &Settings) -> u64 {
+    let values = [
+        day,
+        settings.colors.get() as u64,
+        settings.guesses.get() as u64,
+        settings.holes.get() as u64,
+        settings.no_duplicate as u64,
+    ];
+    values
+        .iter()
+        .fold(0, |seed, value| splitmix64(seed ^ value))
+}
+
+/// Whether the rules give the same code on the same day, whatever else
+/// differs between them
+fn is_same_puzzle(a: &Settings, b: &Settings) -> bool {
+    a.colors == b.colors
+        && a.guesses == b.guesses
+        && a.holes == b.holes
+        && a.no_duplicate == b.no_duplicate
+        && a.allow_empty == b.allow_empty
+        && a.digits == b.digits
+}
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Random number generator whose output is fixed by its algorithm, so that
+/// a seed gives the same code on every platform and version, unlike the
+/// generators of `rand`
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Uniformly random number below `bound`
+    pub fn below(&mut self, bound: usize) -> usize {
+        let bound = bound as u64;
+        // the largest multiple of `bound` which fits, so that the remainder
+        // is not biased
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let x = self.next();
+            if x < zone {
+                return (x % bound) as usize;
+            }
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        let x = splitmix64(self.0);
+        self.0 = self.0.wrapping_add(GOLDEN_GAMMA);
+        x
+    }
+}
+
+/// Daily puzzle which has been started
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    pub day: u64,
+    /// Rules the puzzle was played with
+    pub settings: Settings,
+    pub guesses: Vec<Guess>,
+    #[serde(default)]
+    pub gave_up: bool,
+    /// Time taken in seconds, which tells whether the time ran out
+    #[serde(default)]
+    pub elapsed: f64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct History {
+    entries: Vec<Entry>,
+}
+
+fn load() -> Result<History> {
+    let path = data_path(DAILY_FILE_NAME)?;
+    if !path.exists() {
+        return Ok(History::default());
+    }
+
+    let file = File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+    let history = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    Ok(history)
+}
+
+fn store(path: &Path, history: &History) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), history)?;
+
+    Ok(())
+}
+
+/// Puzzle of the day which has been started, if any
+pub fn find(day: u64, settings: &Settings) -> Result<Option<Entry>> {
+    let history = load()?;
+    let entry = history
+        .entries
+        .into_iter()
+        .find(|entry| entry.day == day && is_same_puzzle(&entry.settings, settings));
+
+    Ok(entry)
+}
+
+/// Records the puzzle as it stands, replacing the record of an earlier
+/// point of the game
+pub fn record(day: u64, game: &Game) -> Result<()> {
+    let mut history = load()?;
+    history
+        .entries
+        .retain(|entry| !(entry.day == day && is_same_puzzle(&entry.settings, &game.settings)));
+    history.entries.push(Entry {
+        day,
+        settings: game.settings.clone(),
+        guesses: game.guesses.clone(),
+        gave_up: game.gave_up,
+        elapsed: game.stopwatch.elapsed().as_secs_f64(),
+    });
+    store(&data_path(DAILY_FILE_NAME)?, &history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::{NonZeroU64, NonZeroUsize};
+
+    fn settings(colors: usize) -> Settings {
+        Settings {
+            colors: NonZeroUsize::new(colors).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn seed_depends_on_day_and_settings() {
+        assert_eq!(seed(20000, &settings(6)), seed(20000, &settings(6)));
+        assert_ne!(seed(20000, &settings(6)), seed(20001, &settings(6)));
+        assert_ne!(seed(20000, &settings(6)), seed(20000, &settings(7)));
+    }
+
+    #[test]
+    fn same_puzzle_under_other_rules() {
+        let mut other = settings(6);
+        other.hard = true;
+        other.time_limit = NonZeroU64::new(60);
+        assert!(is_same_puzzle(&settings(6), &other));
+
+        other.allow_empty = true;
+        assert!(!is_same_puzzle(&settings(6), &other));
+        assert!(!is_same_puzzle(&settings(6), &settings(7)));
+    }
+
+    #[test]
+    fn first_puzzle() {
+        assert_eq!(puzzle_number(FIRST_DAY), 1);
+        assert_eq!(puzzle_number(FIRST_DAY + 10), 11);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::hook::Summary;
+use crate::{Guess, Hint, Settings};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Event of a game written as a JSON line with --json. Colors are numbered
+/// as in the game, from 1 or as the digits themselves.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum GameEvent<'a> {
+    NewGame {
+        settings: &'a Settings,
+        daily: Option<u64>,
+    },
+    Guess {
+        guess: Vec<usize>,
+        bulls: usize,
+        cows: usize,
+    },
+    /// A command was rejected
+    Error {
+        message: String,
+    },
+    GameOver(Summary<'a>),
+}
+
+impl GameEvent<'_> {
+    pub fn guess(settings: &Settings, guess: &Guess, hint: &Hint) -> Self {
+        Self::Guess {
+            guess: guess.0.iter().map(|&c| settings.color_number(c)).collect(),
+            bulls: hint.bulls,
+            cows: hint.cows,
+        }
+    }
+}
+
+/// Command read as a JSON line with --batch --json
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Command {
+    Guess { guess: Vec<usize> },
+}
+
+pub fn write(mut out: impl Write, event: &GameEvent) -> Result<()> {
+    serde_json::to_writer(&mut out, event)?;
+    writeln!(out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_lines() {
+        let mut out = Vec::new();
+        let hint = Hint { bulls: 1, cows: 2 };
+        let event = GameEvent::guess(&Settings::default(), &Guess(vec![0, 2, 1, 3]), &hint);
+        write(&mut out, &event).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"event\":\"guess\",\"guess\":[1,3,2,4],\"bulls\":1,\"cows\":2}\n"
+        );
+
+        let command: Command =
+            serde_json::from_str(r#"{"command": "guess", "guess": [1, 3, 2, 4]}"#).unwrap();
+        assert_eq!(
+            command,
+            Command::Guess {
+                guess: vec![1, 3, 2, 4]
+            }
+        );
+    }
+}
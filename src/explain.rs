@@ -0,0 +1,232 @@
+//! Explains why a code cannot be the solution given the hints so far
+
+use crate::{calc_hint, Guess, Hint};
+
+/// How many pegs of a color a code would need
+#[derive(Debug, PartialEq)]
+pub enum Requirement {
+    None,
+    AtLeast(usize),
+    Exactly(usize),
+}
+
+/// Reason why a code is inconsistent with an earlier hint
+#[derive(Debug, PartialEq)]
+pub enum Contradiction {
+    /// The code has a color in a hole where the hint rules it out
+    Position { color: usize, hole: usize },
+    /// The code has a wrong number of pegs of a color
+    Color {
+        color: usize,
+        requirement: Requirement,
+    },
+    /// The code shares a wrong number of colors with the guess
+    Common { required: usize, actual: usize },
+    /// The code has a wrong number of pegs in the same positions as the guess
+    Bulls { required: usize, actual: usize },
+}
+
+/// Contradiction with an earlier guess
+#[derive(Debug, PartialEq)]
+pub struct Explanation {
+    /// Index of the guess whose hint is contradicted
+    pub guess: usize,
+    pub contradiction: Contradiction,
+}
+
+impl Explanation {
+    /// e.g. "Contradicts guess 3: would require at least 2 reds"
+    pub fn describe(&self, color_name: impl Fn(usize) -> String) -> String {
+        let requirement = match &self.contradiction {
+            Contradiction::Position { color, hole } => {
+                format!(
+                    "would require no {} in hole {}",
+                    color_name(*color),
+                    hole + 1
+                )
+            }
+            Contradiction::Color { color, requirement } => {
+                let name = color_name(*color);
+                match requirement {
+                    Requirement::None => format!("would require no {}", name),
+                    Requirement::AtLeast(n) => {
+                        format!("would require at least {}", count(*n, &name))
+                    }
+                    Requirement::Exactly(n) => {
+                        format!("would require exactly {}", count(*n, &name))
+                    }
+                }
+            }
+            Contradiction::Common { required, actual } => format!(
+                "would require {} in common, not {}",
+                count(*required, "color"),
+                actual
+            ),
+            Contradiction::Bulls { required, actual } => format!(
+                "would require {} in the same position, not {}",
+                count(*required, "peg"),
+                actual
+            ),
+        };
+        format!("Contradicts guess {}: {}", self.guess + 1, requirement)
+    }
+}
+
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+/// Finds the first earlier hint which `code` contradicts, or `None` if
+/// `code` could be the solution
+pub fn explain(
+    code: &Guess,
+    guesses: &[Guess],
+    hints: &[Hint],
+    num_colors: usize,
+) -> Option<Explanation> {
+    guesses
+        .iter()
+        .zip(hints.iter())
+        .enumerate()
+        .find_map(|(i, (guess, hint))| {
+            let actual = calc_hint(guess, code, num_colors);
+            if actual == *hint {
+                None
+            } else {
+                Some(Explanation {
+                    guess: i,
+                    contradiction: contradiction(code, guess, hint, &actual, num_colors),
+                })
+            }
+        })
+}
+
+fn contradiction(
+    code: &Guess,
+    guess: &Guess,
+    hint: &Hint,
+    actual: &Hint,
+    num_colors: usize,
+) -> Contradiction {
+    let required_common = hint.bulls + hint.cows;
+    let actual_common = actual.bulls + actual.cows;
+
+    if required_common != actual_common {
+        let mut guess_counts = vec![0usize; num_colors];
+        let mut code_counts = vec![0usize; num_colors];
+        for c in &guess.0 {
+            guess_counts[*c] += 1;
+        }
+        for c in &code.0 {
+            code_counts[*c] += 1;
+        }
+        let colors: Vec<_> = (0..num_colors).filter(|c| guess_counts[*c] > 0).collect();
+
+        if required_common == 0 {
+            // none of the colors of the guess is in the solution
+            if let Some(color) = colors.iter().copied().find(|c| code_counts[*c] > 0) {
+                return Contradiction::Color {
+                    color,
+                    requirement: Requirement::None,
+                };
+            }
+        } else if required_common == guess.0.len() {
+            // the solution is a permutation of the guess
+            if let Some(color) = colors
+                .iter()
+                .copied()
+                .find(|c| code_counts[*c] < guess_counts[*c])
+            {
+                return Contradiction::Color {
+                    color,
+                    requirement: Requirement::AtLeast(guess_counts[color]),
+                };
+            }
+        } else if let [color] = colors[..] {
+            // the guess consists of a single color
+            return Contradiction::Color {
+                color,
+                requirement: Requirement::Exactly(required_common),
+            };
+        }
+
+        return Contradiction::Common {
+            required: required_common,
+            actual: actual_common,
+        };
+    }
+
+    if hint.bulls == 0 {
+        if let Some(hole) = (0..code.0.len()).find(|i| code.0[*i] == guess.0[*i]) {
+            return Contradiction::Position {
+                color: code.0[hole],
+                hole,
+            };
+        }
+    }
+
+    Contradiction::Bulls {
+        required: hint.bulls,
+        actual: actual.bulls,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explain_one(code: &[usize], guess: &[usize], solution: &[usize]) -> Option<Explanation> {
+        let guess = Guess(guess.to_vec());
+        let hint = calc_hint(&guess, &Guess(solution.to_vec()), 6);
+        explain(&Guess(code.to_vec()), &[guess], &[hint], 6)
+    }
+
+    #[test]
+    fn consistent() {
+        assert_eq!(
+            explain_one(&[0, 1, 2, 3], &[0, 0, 1, 1], &[0, 1, 2, 3]),
+            None
+        );
+    }
+
+    #[test]
+    fn single_color() {
+        let explanation = explain_one(&[1, 2, 3, 4], &[1, 1, 1, 1], &[1, 1, 2, 3]).unwrap();
+        assert_eq!(
+            explanation.contradiction,
+            Contradiction::Color {
+                color: 1,
+                requirement: Requirement::Exactly(2)
+            }
+        );
+        assert_eq!(
+            explanation.describe(|_| "red".to_string()),
+            "Contradicts guess 1: would require exactly 2 reds"
+        );
+    }
+
+    #[test]
+    fn absent_color() {
+        let explanation = explain_one(&[0, 2, 3, 4], &[0, 1, 0, 1], &[2, 3, 4, 5]).unwrap();
+        assert_eq!(
+            explanation.contradiction,
+            Contradiction::Color {
+                color: 0,
+                requirement: Requirement::None
+            }
+        );
+    }
+
+    #[test]
+    fn position() {
+        let explanation = explain_one(&[1, 0, 4, 3], &[0, 1, 4, 3], &[3, 0, 1, 4]).unwrap();
+        assert_eq!(
+            explanation.contradiction,
+            Contradiction::Position { color: 4, hole: 2 }
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::borrow::Cow;
+
+/// Characters used to draw pegs
+#[derive(Debug, Clone)]
+pub struct Glyphs {
+    /// Draw code pegs as their numbers so that they can be told apart
+    /// without colors
+    pub numbered: bool,
+    /// Number code pegs from 0 as the digits of the numeric variant
+    pub digits: bool,
+    /// Whether all the glyphs are ASCII, for terminals without Unicode
+    pub ascii: bool,
+    pub peg: &'static str,
+    /// Key peg for correct color, correct position
+    pub bull: &'static str,
+    /// Key peg for correct color, wrong position
+    pub cow: &'static str,
+    /// Empty hole
+    pub empty: &'static str,
+    /// Code peg left blank on purpose, with `--allow-empty`
+    pub blank: &'static str,
+    /// Bar of the guess distribution
+    pub bar: &'static str,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self {
+            numbered: false,
+            digits: false,
+            ascii: false,
+            peg: "●",
+            bull: "●",
+            cow: "●",
+            empty: "∙",
+            blank: "◌",
+            bar: "█",
+        }
+    }
+}
+
+impl Glyphs {
+    /// Glyphs distinguishable on monochrome terminals and by colorblind
+    /// players
+    pub fn symbols() -> Self {
+        Self {
+            numbered: true,
+            cow: "○",
+            ..Self::default()
+        }
+    }
+
+    /// Digits of the numeric variant
+    pub fn digits() -> Self {
+        Self {
+            numbered: true,
+            digits: true,
+            cow: "○",
+            ..Self::default()
+        }
+    }
+
+    /// The glyphs replaced with ASCII characters, keeping the numbers
+    pub fn ascii(self) -> Self {
+        Self {
+            ascii: true,
+            peg: "O",
+            bull: "*",
+            cow: "o",
+            empty: ".",
+            blank: "_",
+            bar: "#",
+            ..self
+        }
+    }
+
+    /// Glyph of a code peg of the color
+    pub fn code(&self, color: usize) -> Cow<'static, str> {
+        if self.numbered {
+            let number = if self.digits { color } else { color + 1 };
+            number.to_string().into()
+        } else {
+            self.peg.into()
+        }
+    }
+}
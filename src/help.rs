@@ -0,0 +1,221 @@
+//! In-game reference of the keys, the options of the game and the rules of
+//! the hints
+
+use crate::input::InputMode;
+use crate::keymap::KeyAction;
+use crate::stopwatch::format_duration;
+use crate::suspend;
+use crate::{Backend, Game};
+use std::iter;
+use std::time::Duration;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::widgets::{Block, Borders, Clear, Text};
+use tui::Frame;
+
+/// Width of the overlay including its border
+const WIDTH: u16 = 60;
+
+/// Margin between the keys and what they do
+const KEY_MARGIN: usize = 2;
+
+/// Keys available in the game and what they do
+fn keys(game: &Game) -> Vec<(String, String)> {
+    let colors = (
+        game.keys.color_keys(&game.settings),
+        if game.settings.digits {
+            "Enter a digit".to_owned()
+        } else {
+            "Select a color".to_owned()
+        },
+    );
+    let typed = match game.input.mode() {
+        InputMode::Numbers => None,
+        InputMode::Initials => Some((
+            "letters".to_owned(),
+            "Select a color by the letter under it".to_owned(),
+        )),
+        InputMode::Names => Some((
+            "names".to_owned(),
+            "Type a color name; tab completes it".to_owned(),
+        )),
+    };
+    let macros = game
+        .macros
+        .iter()
+        .map(|(key, pattern)| (key.to_string(), format!("Fill the row with {}", pattern)));
+
+    let actions = KeyAction::ALL
+        .iter()
+        .filter(|&&action| match action {
+            KeyAction::Overlay => game.assistant.is_some(),
+            KeyAction::NewGame => game.check_new_game().is_ok(),
+            KeyAction::Save => game.save_path.is_some(),
+            KeyAction::Analyze | KeyAction::Share => game.post_game,
+            KeyAction::Pause => !game.post_game,
+            KeyAction::Suspend => suspend::IS_SUPPORTED,
+            _ => true,
+        })
+        .map(|&action| (game.keys.keys(action), action.description().to_owned()));
+
+    iter::once(colors)
+        .chain(typed)
+        .chain(macros)
+        .chain(actions)
+        .collect()
+}
+
+/// Options the game is played with
+fn options(game: &Game) -> Vec<String> {
+    let settings = &game.settings;
+    let mut options = vec![format!(
+        "{} {}, {} holes, {} guesses",
+        settings.colors,
+        if settings.digits { "digits" } else { "colors" },
+        settings.holes,
+        settings.guesses
+    )];
+    options.push(if settings.no_duplicate {
+        format!("No {} appears twice in the code", game.peg_noun())
+    } else {
+        format!("{}s may appear more than once", capitalize(game.peg_noun()))
+    });
+    if settings.hard {
+        options.push("Guesses must agree with all the hints so far".to_owned());
+    }
+    if settings.allow_empty {
+        options.push(format!(
+            "Holes of the code may be blank, entered with {}",
+            game.keys.color_key(settings.colors.get(), settings)
+        ));
+    }
+    if settings.blind {
+        options.push("Hints do not tell correct positions from wrong ones".to_owned());
+    }
+    if settings.static_hints {
+        options.push(format!(
+            "Hints are hidden until guess {}",
+            settings.guesses.get().saturating_sub(1)
+        ));
+    }
+    if game.marathon.is_some() {
+        options.push("Marathon: a new code follows each broken one".to_owned());
+    }
+    if let Some(limit) = settings.time_limit {
+        options.push(format!(
+            "Time limit {}",
+            format_duration(Duration::from_secs(limit.get()))
+        ));
+    }
+    if let Some(turn_time) = settings.turn_time {
+        options.push(format!(
+            "{} per guess, or the turn is {}",
+            format_duration(Duration::from_secs(turn_time.get())),
+            if settings.forfeit_turn {
+                "lost"
+            } else {
+                "played at random"
+            }
+        ));
+    }
+    options
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Draws the help over the game in the middle of the screen
+pub fn draw(game: &Game, f: &mut Frame<Backend>) {
+    let bull_style = Style::default().fg(game.theme.bull);
+    let cow_style = Style::default().fg(game.theme.cow);
+    let noun = game.peg_noun();
+
+    let keys = keys(game);
+    let key_width = keys.iter().map(|(key, _)| key.len()).max().unwrap_or(0) + KEY_MARGIN;
+    let mut text = vec![Text::raw("Keys\n")];
+    for (key, action) in keys {
+        text.push(Text::raw(format!(
+            "  {:width$}{}\n",
+            key,
+            action,
+            width = key_width
+        )));
+    }
+
+    text.push(Text::raw("\nOptions\n"));
+    for option in options(game) {
+        text.push(Text::raw(format!("  {}\n", option)));
+    }
+
+    text.extend(vec![
+        Text::raw("\nHints\n  "),
+        Text::styled(game.glyphs.bull, bull_style),
+        Text::raw(format!(" Correct {}, correct position\n  ", noun)),
+        Text::styled(game.glyphs.cow, cow_style),
+        Text::raw(format!(" Correct {}, wrong position\n", noun)),
+        Text::raw(format!(
+            "  Each {} counts once, and the order of the hint\n  does not follow the code\n",
+            noun
+        )),
+        Text::raw("\nPress any key to close"),
+    ]);
+
+    let lines = text
+        .iter()
+        .map(|text| match text {
+            Text::Raw(s) | Text::Styled(s, _) => s.matches('\n').count(),
+        })
+        .sum::<usize>()
+        + 1;
+
+    let size = f.size();
+    let width = WIDTH.min(size.width);
+    let height = (lines as u16 + 2).min(size.height);
+    let area = Rect::new(
+        (size.width - width) / 2,
+        (size.height - height) / 2,
+        width,
+        height,
+    );
+
+    f.render_widget(Clear, area);
+    // box-drawing characters are not ASCII
+    let borders = if game.glyphs.ascii {
+        Borders::NONE
+    } else {
+        Borders::ALL
+    };
+    let block = Block::default()
+        .borders(borders)
+        .title(" Help ")
+        .border_style(game.theme.text)
+        .style(game.theme.text);
+    f.render_widget(game.paragraph(text.iter()).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Settings;
+
+    #[test]
+    fn list_available_keys() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        let has_key = |game: &Game, key: &str| keys(game).iter().any(|(k, _)| k == key);
+        assert!(has_key(&game, "1-6"));
+        assert!(has_key(&game, "enter/space"));
+        assert!(has_key(&game, "n"));
+        assert!(!has_key(&game, "o"));
+        assert!(!has_key(&game, "ctrl+s"));
+
+        game.daily = Some(0);
+        game.enable_assist();
+        assert!(!has_key(&game, "n"));
+        assert!(has_key(&game, "o"));
+    }
+}
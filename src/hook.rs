@@ -0,0 +1,202 @@
+use crate::{Game, Settings, State};
+use anyhow::{Context, Result};
+use crossbeam_channel::{Receiver, Sender};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Default time to wait for the hook before killing it
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interval of checking whether the hook has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Result of a finished game passed to the hook. Colors are numbered from 1
+/// as in the game.
+#[derive(Debug, Serialize)]
+pub struct Summary<'a> {
+    pub won: bool,
+    pub settings: &'a Settings,
+    pub solution: Vec<usize>,
+    pub guesses: Vec<Vec<usize>>,
+    /// `[bulls, cows]` of each guess
+    pub hints: Vec<[usize; 2]>,
+    pub seconds: f64,
+    pub score: Option<u64>,
+    pub daily: Option<u64>,
+}
+
+impl<'a> Summary<'a> {
+    pub fn new(game: &'a Game) -> Self {
+        let numbered = |colors: &[usize]| {
+            colors
+                .iter()
+                .map(|&c| game.settings.color_number(c))
+                .collect()
+        };
+        Self {
+            won: game.status() == State::Won,
+            settings: &game.settings,
+            solution: numbered(&game.solution.0),
+            guesses: game.guesses.iter().map(|g| numbered(&g.0)).collect(),
+            hints: game.hints.iter().map(|h| [h.bulls, h.cows]).collect(),
+            seconds: game.stopwatch.elapsed().as_secs_f64(),
+            score: game.score.map(|(score, _)| score),
+            daily: game.daily,
+        }
+    }
+}
+
+/// Shell command run when a game finishes
+#[derive(Debug)]
+pub struct Hook {
+    command: String,
+    timeout: Duration,
+    /// Runs in the background which may not have exited yet
+    running: Vec<JoinHandle<()>>,
+    failures: (Sender<anyhow::Error>, Receiver<anyhow::Error>),
+}
+
+impl Hook {
+    pub fn new(command: String, timeout: Duration) -> Self {
+        Self {
+            command,
+            timeout,
+            running: Vec::new(),
+            failures: crossbeam_channel::unbounded(),
+        }
+    }
+
+    /// Runs the command with the summary on stdin, killing it when it does
+    /// not exit in time
+    pub fn run(&self, summary: &Summary) -> Result<()> {
+        execute(
+            &self.command,
+            self.timeout,
+            &serde_json::to_string(summary)?,
+        )
+    }
+
+    /// Runs the command in the background so that the game goes on
+    /// meanwhile. A failure is sent to `failures`.
+    pub fn spawn(&mut self, summary: &Summary) -> Result<()> {
+        let json = serde_json::to_string(summary)?;
+        let command = self.command.clone();
+        let timeout = self.timeout;
+        let tx = self.failures.0.clone();
+        self.running.retain(|run| !run.is_finished());
+        self.running.push(std::thread::spawn(move || {
+            if let Err(err) = execute(&command, timeout, &json) {
+                let _ = tx.send(err);
+            }
+        }));
+        Ok(())
+    }
+
+    /// Failures of the runs in the background
+    pub fn failures(&self) -> &Receiver<anyhow::Error> {
+        &self.failures.1
+    }
+
+    /// Waits for the runs in the background, which would be cut short when
+    /// the game exits, and returns their failures not received yet
+    pub fn wait(&mut self) -> Vec<anyhow::Error> {
+        for run in self.running.drain(..) {
+            let _ = run.join();
+        }
+        self.failures.1.try_iter().collect()
+    }
+}
+
+/// Output of the command is discarded, as stdout may carry the events with
+/// --json and the terminal is taken by the game
+fn execute(command: &str, timeout: Duration, json: &str) -> Result<()> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to run \"{}\"", command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        match writeln!(stdin, "{}", json) {
+            // the command does not have to read the summary
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(err.into());
+            }
+            _ => (),
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if status.success() {
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!("\"{}\" exited with {}", command, status));
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow::anyhow!(
+                "\"{}\" did not exit in {} seconds",
+                command,
+                timeout.as_secs_f64()
+            ));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn run(command: &str, timeout: Duration) -> Result<()> {
+        let game = Game::with_seed(Settings::default(), 0);
+        Hook::new(command.to_owned(), timeout).run(&Summary::new(&game))
+    }
+
+    #[test]
+    fn receives_summary() {
+        assert!(run("grep -q '\"won\":false'", DEFAULT_TIMEOUT).is_ok());
+        assert!(run("grep -q '\"won\":true'", DEFAULT_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn runs_in_background() {
+        let game = Game::with_seed(Settings::default(), 0);
+        let mut hook = Hook::new("echo output; exit 1".to_owned(), DEFAULT_TIMEOUT);
+        hook.spawn(&Summary::new(&game)).unwrap();
+        hook.spawn(&Summary::new(&game)).unwrap();
+        assert_eq!(hook.wait().len(), 2);
+        assert!(hook.failures().is_empty());
+    }
+
+    #[test]
+    fn killed_on_timeout() {
+        let start = Instant::now();
+        assert!(run("sleep 10", Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
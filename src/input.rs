@@ -0,0 +1,306 @@
+//! Ways of typing the colors besides the number keys: a letter of each color
+//! name, or the name itself, completed with Tab
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Names of the modes on the command line and in the config
+pub const MODES: &[&str] = &["numbers", "initials", "names"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputMode {
+    /// Number keys only
+    #[default]
+    Numbers,
+    /// A letter of each color name, which is the initial unless another
+    /// color has it
+    Initials,
+    /// Color names, or their starts completed with Tab or Enter
+    Names,
+}
+
+impl FromStr for InputMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "numbers" => Ok(Self::Numbers),
+            "initials" => Ok(Self::Initials),
+            "names" => Ok(Self::Names),
+            _ => Err(anyhow::anyhow!(
+                "unknown input mode \"{}\"; choose from {}",
+                s,
+                MODES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Colors typed as letters, which go along with the number keys
+#[derive(Debug, Clone, Default)]
+pub struct ColorInput {
+    mode: InputMode,
+    /// Names of the colors in lowercase, or `None` for a color which cannot
+    /// be typed as its name is shared or is not a single word
+    names: Vec<Option<String>>,
+    /// Letter of each color in the initials mode
+    initials: Vec<Option<char>>,
+    /// Letters bound to actions, macros or colors by position, which keep
+    /// doing so. A name starting with one is typed with Shift.
+    reserved: Vec<char>,
+    /// Start of a name typed so far
+    typed: String,
+}
+
+impl ColorInput {
+    /// Input of the colors with the names in order, leaving alone the keys
+    /// for which `is_reserved` holds
+    pub fn new(mode: InputMode, names: &[String], is_reserved: impl Fn(char) -> bool) -> Self {
+        let names: Vec<_> = names
+            .iter()
+            .map(|name| {
+                let name = name.to_lowercase();
+                let unique = names
+                    .iter()
+                    .filter(|other| other.to_lowercase() == name)
+                    .count()
+                    == 1;
+                Some(name).filter(|name| unique && name.chars().all(|c| c.is_ascii_alphabetic()))
+            })
+            .collect();
+
+        let reserved: Vec<_> = ('a'..='z')
+            .chain('A'..='Z')
+            .filter(|&c| is_reserved(c))
+            .collect();
+
+        // the first letter of the name taken neither by the colors before
+        // nor by the keys
+        let mut initials: Vec<Option<char>> = Vec::new();
+        for name in &names {
+            let initial = name
+                .iter()
+                .flat_map(|name| name.chars())
+                .find(|c| !initials.contains(&Some(*c)) && !reserved.contains(c));
+            initials.push(initial);
+        }
+
+        Self {
+            mode,
+            names,
+            initials,
+            reserved,
+            typed: String::new(),
+        }
+    }
+
+    pub fn mode(&self) -> InputMode {
+        self.mode
+    }
+
+    /// Start of a name typed so far
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    /// What the legend shows for the color besides its number: the letter
+    /// of the color, or the shortest start which tells its name from the
+    /// others
+    pub fn abbreviation(&self, color: usize) -> Option<String> {
+        match self.mode {
+            InputMode::Numbers => None,
+            InputMode::Initials => self.initials[color].map(String::from),
+            InputMode::Names => {
+                let name = self.names[color].as_ref()?;
+                let start = (1..=name.len())
+                    .map(|len| &name[..len])
+                    .find(|start| self.candidates(start).len() == 1)?;
+                // with Shift if the initial is taken
+                if self.reserved.contains(&name.chars().next()?) {
+                    Some(start[..1].to_uppercase() + &start[1..])
+                } else {
+                    Some(start.to_owned())
+                }
+            }
+        }
+    }
+
+    /// Colors whose names start with `start`
+    fn candidates(&self, start: &str) -> Vec<usize> {
+        self.names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.as_ref().is_some_and(|name| name.starts_with(start)))
+            .map(|(color, _)| color)
+            .collect()
+    }
+
+    /// Colors whose names start with what is typed so far
+    pub fn completions(&self) -> Vec<usize> {
+        self.candidates(&self.typed)
+    }
+
+    /// Whether the key goes to typing a color rather than to an action or a
+    /// number key
+    pub fn takes(&self, event: KeyEvent) -> bool {
+        if event.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match (self.mode, event.code) {
+            (InputMode::Initials, KeyCode::Char(c)) if self.reserved.contains(&c) => false,
+            (InputMode::Names, KeyCode::Char(c))
+                if self.reserved.contains(&c) && self.typed.is_empty() =>
+            {
+                false
+            }
+            (InputMode::Initials, KeyCode::Char(c)) => self.initial_color(c).is_some(),
+            (InputMode::Names, KeyCode::Char(c)) => {
+                let mut typed = self.typed.clone();
+                typed.extend(c.to_lowercase());
+                !self.candidates(&typed).is_empty()
+            }
+            (
+                InputMode::Names,
+                KeyCode::Tab | KeyCode::Enter | KeyCode::Backspace | KeyCode::Esc,
+            ) => !self.typed.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Handles a key which the input `takes`, and returns the color it
+    /// selects if any
+    pub fn on_key(&mut self, event: KeyEvent) -> Option<usize> {
+        match event.code {
+            KeyCode::Char(c) if self.mode == InputMode::Initials => self.initial_color(c),
+            KeyCode::Char(c) => {
+                self.typed.extend(c.to_lowercase());
+                // a whole name is taken at once unless it starts another
+                match self.completions().as_slice() {
+                    [color] if self.names[*color].as_deref() == Some(self.typed.as_str()) => {
+                        self.typed.clear();
+                        Some(*color)
+                    }
+                    _ => None,
+                }
+            }
+            KeyCode::Tab | KeyCode::Enter => self.complete(),
+            KeyCode::Backspace => {
+                self.typed.pop();
+                None
+            }
+            KeyCode::Esc => {
+                self.typed.clear();
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Selects the color if only one name starts with what is typed, or
+    /// else types as much as the names have in common
+    fn complete(&mut self) -> Option<usize> {
+        let completions = self.completions();
+        if let [color] = completions.as_slice() {
+            self.typed.clear();
+            return Some(*color);
+        }
+
+        let names: Vec<&str> = completions
+            .iter()
+            .filter_map(|&color| self.names[color].as_deref())
+            .collect();
+        if let Some(first) = names.first() {
+            let common = (self.typed.len()..=first.len())
+                .rev()
+                .map(|len| &first[..len])
+                .find(|start| names.iter().all(|name| name.starts_with(start)));
+            if let Some(common) = common {
+                self.typed = common.to_owned();
+            }
+        }
+        None
+    }
+
+    fn initial_color(&self, c: char) -> Option<usize> {
+        let c = c.to_lowercase().next()?;
+        self.initials.iter().position(|&initial| initial == Some(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn names() -> Vec<String> {
+        ["blue", "red", "green", "black", "blank", "color 6"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn initials() {
+        let mut input = ColorInput::new(InputMode::Initials, &names(), |_| false);
+        let abbreviations: Vec<_> = (0..6).map(|color| input.abbreviation(color)).collect();
+        let expected = [Some("b"), Some("r"), Some("g"), Some("l"), Some("a"), None];
+        assert_eq!(
+            abbreviations,
+            expected
+                .iter()
+                .map(|a| a.map(str::to_owned))
+                .collect::<Vec<_>>()
+        );
+
+        assert!(input.takes(key(KeyCode::Char('G'))));
+        assert_eq!(input.on_key(key(KeyCode::Char('G'))), Some(2));
+        assert!(!input.takes(key(KeyCode::Char('q'))));
+        assert!(!input.takes(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL)));
+
+        // keys of actions keep their letters
+        let input = ColorInput::new(InputMode::Initials, &names(), |c| c == 'g');
+        assert_eq!(input.abbreviation(2), Some("e".to_owned()));
+        assert!(!input.takes(key(KeyCode::Char('g'))));
+    }
+
+    #[test]
+    fn names_with_completion() {
+        let mut input = ColorInput::new(InputMode::Names, &names(), |c| c == 'g');
+        assert_eq!(input.abbreviation(0), Some("blu".to_owned()));
+        assert_eq!(input.abbreviation(1), Some("r".to_owned()));
+        assert_eq!(input.abbreviation(5), None);
+
+        // "b" starts three names, and "bl" is what they have in common
+        assert!(!input.takes(key(KeyCode::Enter)));
+        assert_eq!(input.on_key(key(KeyCode::Char('b'))), None);
+        assert_eq!(input.on_key(key(KeyCode::Tab)), None);
+        assert_eq!(input.typed(), "bl");
+        assert_eq!(input.completions(), vec![0, 3, 4]);
+        assert!(!input.takes(key(KeyCode::Char('x'))));
+
+        assert_eq!(input.on_key(key(KeyCode::Char('u'))), None);
+        assert_eq!(input.on_key(key(KeyCode::Tab)), Some(0));
+        assert_eq!(input.typed(), "");
+
+        // a whole name needs no Tab
+        for c in "re".chars() {
+            assert_eq!(input.on_key(key(KeyCode::Char(c))), None);
+        }
+        assert_eq!(input.on_key(key(KeyCode::Char('d'))), Some(1));
+
+        // a name starting with the key of an action is typed with Shift
+        assert_eq!(input.abbreviation(2), Some("G".to_owned()));
+        assert!(!input.takes(key(KeyCode::Char('g'))));
+        assert!(input.takes(key(KeyCode::Char('G'))));
+        input.on_key(key(KeyCode::Char('G')));
+        assert!(input.takes(key(KeyCode::Char('r'))));
+        assert!(input.takes(key(KeyCode::Esc)));
+        input.on_key(key(KeyCode::Esc));
+        assert_eq!(input.typed(), "");
+    }
+}
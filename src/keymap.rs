@@ -0,0 +1,477 @@
+//! Keys bound to the actions of the game, remappable in the `[keys]` table
+//! of the config
+
+use crate::Settings;
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Quits whatever the bindings are, so that a broken config cannot trap the
+/// player
+const QUIT_ALWAYS: Key = Key {
+    code: KeyCode::Char('c'),
+    ctrl: true,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyAction {
+    Submit,
+    Undo,
+    CursorLeft,
+    CursorRight,
+    Delete,
+    Insert,
+    Quit,
+    Save,
+    Legend,
+    Overlay,
+    ScrollUp,
+    ScrollDown,
+    GiveUp,
+    NewGame,
+    Help,
+    Analyze,
+    Share,
+    Pause,
+    Suspend,
+}
+
+impl KeyAction {
+    pub const ALL: &'static [Self] = &[
+        Self::Submit,
+        Self::Undo,
+        Self::Delete,
+        Self::CursorLeft,
+        Self::CursorRight,
+        Self::Insert,
+        Self::ScrollUp,
+        Self::ScrollDown,
+        Self::Legend,
+        Self::Overlay,
+        Self::GiveUp,
+        Self::NewGame,
+        Self::Save,
+        Self::Pause,
+        Self::Suspend,
+        Self::Analyze,
+        Self::Share,
+        Self::Help,
+        Self::Quit,
+    ];
+
+    /// Name in the config
+    fn name(self) -> &'static str {
+        match self {
+            Self::Submit => "submit",
+            Self::Undo => "undo",
+            Self::CursorLeft => "cursor-left",
+            Self::CursorRight => "cursor-right",
+            Self::Delete => "delete",
+            Self::Insert => "insert",
+            Self::Quit => "quit",
+            Self::Save => "save",
+            Self::Legend => "legend",
+            Self::Overlay => "overlay",
+            Self::ScrollUp => "scroll-up",
+            Self::ScrollDown => "scroll-down",
+            Self::GiveUp => "give-up",
+            Self::NewGame => "new-game",
+            Self::Help => "help",
+            Self::Analyze => "analyze",
+            Self::Share => "share",
+            Self::Pause => "pause",
+            Self::Suspend => "suspend",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Submit => "Make a guess",
+            Self::Undo => "Remove the peg before the cursor",
+            Self::CursorLeft => "Move the cursor left",
+            Self::CursorRight => "Move the cursor right",
+            Self::Delete => "Remove the peg at the cursor",
+            Self::Insert => "Switch between replacing and inserting pegs",
+            Self::Quit => "Quit",
+            Self::Save => "Save the game",
+            Self::Legend => "Show or hide the legend",
+            Self::Overlay => "Show or hide the likely colors",
+            Self::ScrollUp => "Scroll the board up",
+            Self::ScrollDown => "Scroll the board down",
+            Self::GiveUp => "Give up",
+            Self::NewGame => "Start a new game",
+            Self::Help => "Show this help",
+            Self::Analyze => "Analyze the guesses",
+            Self::Share => "Copy the hints to the clipboard to share",
+            Self::Pause => "Pause the game and hide the board",
+            Self::Suspend => "Suspend to the shell",
+        }
+    }
+
+    /// Whether the action is only available once the game is over, when
+    /// its keys do not select colors or run macros
+    fn is_post_game(self) -> bool {
+        matches!(self, Self::Analyze | Self::Share)
+    }
+
+    fn default_keys(self) -> &'static str {
+        match self {
+            Self::Submit => "enter space",
+            Self::Undo => "backspace",
+            Self::CursorLeft => "left",
+            Self::CursorRight => "right",
+            Self::Delete => "delete",
+            Self::Insert => "insert",
+            Self::Quit => "q esc",
+            Self::Save => "ctrl+s",
+            Self::Legend => "l",
+            Self::Overlay => "o",
+            Self::ScrollUp => "pageup",
+            Self::ScrollDown => "pagedown",
+            Self::GiveUp => "g",
+            Self::NewGame => "n",
+            Self::Help => "?",
+            Self::Analyze => "a",
+            Self::Share => "s",
+            Self::Pause => "p",
+            Self::Suspend => "ctrl+z",
+        }
+    }
+}
+
+/// Key with or without Ctrl. Other modifiers are ignored, since terminals
+/// report Shift inconsistently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    ctrl: bool,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self {
+            code: event.code,
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        }
+    }
+}
+
+/// Names of the keys other than characters
+static KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("space", KeyCode::Char(' ')),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+];
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (ctrl, name) = match s.strip_prefix("ctrl+") {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => KEY_NAMES
+                .iter()
+                .find(|(key_name, _)| *key_name == name)
+                .map(|(_, code)| *code)
+                .ok_or_else(|| anyhow::anyhow!("unknown key \"{}\"", s))?,
+        };
+        Ok(Self { code, ctrl })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("ctrl+")?;
+        }
+        match KEY_NAMES.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => f.write_str(name),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{}", c),
+                code => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+impl Key {
+    /// Character typed without Ctrl
+    fn char(self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c) if !self.ctrl => Some(c),
+            _ => None,
+        }
+    }
+}
+
+/// Keys bound to the actions and to the colors
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: BTreeMap<KeyAction, Vec<Key>>,
+    /// Keys selecting the colors in order, instead of the number keys. Not
+    /// used for digits, which are typed as they are.
+    colors: Option<Vec<char>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = KeyAction::ALL
+            .iter()
+            .map(|&action| (action, parse_keys(action.default_keys()).unwrap()))
+            .collect();
+        Self {
+            bindings,
+            colors: None,
+        }
+    }
+}
+
+fn parse_keys(keys: &str) -> Result<Vec<Key>> {
+    keys.split_whitespace().map(str::parse).collect()
+}
+
+impl KeyMap {
+    /// Parses the `[keys]` table of the config, where each action has keys
+    /// separated by spaces. Actions not in the table keep their default keys.
+    pub fn parse(definitions: &BTreeMap<String, String>) -> Result<Self> {
+        let mut keymap = Self::default();
+        for (name, keys) in definitions {
+            if name == "colors" {
+                let colors: Vec<_> = keys.chars().filter(|c| !c.is_whitespace()).collect();
+                if colors.is_empty() {
+                    return Err(anyhow::anyhow!("no keys are given for colors"));
+                }
+                keymap.colors = Some(colors);
+                continue;
+            }
+
+            let action = KeyAction::ALL
+                .iter()
+                .copied()
+                .find(|action| action.name() == name)
+                .ok_or_else(|| anyhow::anyhow!("unknown action \"{}\"", name))?;
+            let keys =
+                parse_keys(keys).with_context(|| format!("invalid keys for \"{}\"", name))?;
+            if keys.is_empty() {
+                return Err(anyhow::anyhow!("no keys are given for \"{}\"", name));
+            }
+            keymap.bindings.insert(action, keys);
+        }
+
+        keymap.check_conflicts()?;
+        Ok(keymap)
+    }
+
+    fn check_conflicts(&self) -> Result<()> {
+        let bound: Vec<_> = self
+            .bindings
+            .iter()
+            .flat_map(|(action, keys)| keys.iter().map(move |key| (*key, *action)))
+            .collect();
+
+        for ((key, a), (other, b)) in bound.iter().tuple_combinations() {
+            if key == other {
+                return Err(anyhow::anyhow!(
+                    "key \"{}\" is bound to both \"{}\" and \"{}\"",
+                    key,
+                    a.name(),
+                    b.name()
+                ));
+            }
+        }
+        for (key, action) in &bound {
+            if *key == QUIT_ALWAYS && *action != KeyAction::Quit {
+                return Err(anyhow::anyhow!(
+                    "key \"{}\" always quits and cannot be bound to \"{}\"",
+                    key,
+                    action.name()
+                ));
+            }
+            if let (Some(c), false) = (key.char(), action.is_post_game()) {
+                if self.is_color_key(c) || c.is_ascii_digit() {
+                    return Err(anyhow::anyhow!(
+                        "key \"{}\" selects a color and cannot be bound to \"{}\"",
+                        key,
+                        action.name()
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Action bound to the key, among those available while playing or
+    /// after the game
+    pub fn action(&self, event: KeyEvent, post_game: bool) -> Option<KeyAction> {
+        let key = Key::from(event);
+        if key == QUIT_ALWAYS {
+            return Some(KeyAction::Quit);
+        }
+        self.bindings
+            .iter()
+            .find(|(action, keys)| keys.contains(&key) && (post_game || !action.is_post_game()))
+            .map(|(action, _)| *action)
+    }
+
+    /// Why the character cannot be used for a macro, if so
+    pub fn check_macro_key(&self, c: char) -> Result<(), String> {
+        if c.is_ascii_digit() || self.is_color_key(c) {
+            return Err(format!("key '{}' selects a color", c));
+        }
+        let key = Key {
+            code: KeyCode::Char(c),
+            ctrl: false,
+        };
+        match self
+            .bindings
+            .iter()
+            .find(|(action, keys)| !action.is_post_game() && keys.contains(&key))
+        {
+            Some((action, _)) => Err(format!("key '{}' is bound to \"{}\"", c, action.name())),
+            None => Ok(()),
+        }
+    }
+
+    fn is_color_key(&self, c: char) -> bool {
+        match &self.colors {
+            Some(colors) => colors.contains(&c),
+            None => c.is_ascii_digit(),
+        }
+    }
+
+    /// Color the key selects: `None` if it is not a key of colors, and
+    /// `Some(None)` if it is but there are fewer colors
+    pub fn color(&self, c: char, settings: &Settings) -> Option<Option<usize>> {
+        match &self.colors {
+            Some(colors) if !settings.digits => {
+                let index = colors.iter().position(|&key| key == c)?;
+                Some(Some(index).filter(|&index| index < settings.code_colors()))
+            }
+            _ => {
+                let number = c.to_digit(10)? as usize;
+                Some(settings.number_color(number))
+            }
+        }
+    }
+
+    /// Keys selecting the colors, e.g. "1-6"
+    pub fn color_keys(&self, settings: &Settings) -> String {
+        match &self.colors {
+            Some(colors) if !settings.digits => {
+                colors.iter().take(settings.code_colors()).collect()
+            }
+            _ => settings.color_range(),
+        }
+    }
+
+    /// Key selecting the color
+    pub fn color_key(&self, color: usize, settings: &Settings) -> String {
+        match &self.colors {
+            Some(colors) if !settings.digits => {
+                colors.get(color).map(char::to_string).unwrap_or_default()
+            }
+            _ => settings.color_number(color).to_string(),
+        }
+    }
+
+    /// Keys of the action separated by slashes, e.g. "enter/space"
+    pub fn keys(&self, action: KeyAction) -> String {
+        self.bindings[&action].iter().join("/")
+    }
+
+    /// First key of the action, to mention in prompts
+    pub fn key(&self, action: KeyAction) -> String {
+        self.bindings[&action][0].to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(entries: &[(&str, &str)]) -> Result<KeyMap> {
+        let definitions: BTreeMap<_, _> = entries
+            .iter()
+            .map(|(name, keys)| (name.to_string(), keys.to_string()))
+            .collect();
+        KeyMap::parse(&definitions)
+    }
+
+    #[test]
+    fn remap_keys() {
+        let keymap = parse(&[("quit", "x ctrl+q"), ("submit", "tab"), ("colors", "asdf")]).unwrap();
+        let action = |code, modifiers| keymap.action(KeyEvent { code, modifiers }, false);
+
+        assert_eq!(
+            action(KeyCode::Char('x'), KeyModifiers::empty()),
+            Some(KeyAction::Quit)
+        );
+        assert_eq!(
+            action(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Some(KeyAction::Quit)
+        );
+        assert_eq!(
+            action(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Some(KeyAction::Quit)
+        );
+        assert_eq!(action(KeyCode::Char('q'), KeyModifiers::empty()), None);
+        assert_eq!(
+            action(KeyCode::Tab, KeyModifiers::empty()),
+            Some(KeyAction::Submit)
+        );
+        assert_eq!(keymap.keys(KeyAction::Quit), "x/ctrl+q");
+        assert_eq!(action(KeyCode::Char('a'), KeyModifiers::empty()), None);
+        assert_eq!(
+            keymap.action(KeyCode::Char('a').into(), true),
+            Some(KeyAction::Analyze)
+        );
+
+        let settings = Settings::default();
+        assert_eq!(keymap.color('s', &settings), Some(Some(1)));
+        assert_eq!(keymap.color('1', &settings), None);
+        assert_eq!(keymap.color_keys(&settings), "asdf");
+
+        let keymap = KeyMap::default();
+        assert_eq!(keymap.color('6', &settings), Some(Some(5)));
+        assert_eq!(keymap.color('7', &settings), Some(None));
+        assert_eq!(keymap.keys(KeyAction::Submit), "enter/space");
+    }
+
+    #[test]
+    fn detect_conflicts() {
+        assert!(parse(&[("quit", "l")]).is_err());
+        assert!(parse(&[("quit", "ctrl+s")]).is_err());
+        assert!(parse(&[("help", "1")]).is_err());
+        assert!(parse(&[("undo", "ctrl+c")]).is_err());
+        assert!(parse(&[("colors", "asdfgh"), ("give-up", "a")]).is_err());
+        assert!(parse(&[("flip", "f")]).is_err());
+        assert!(parse(&[("quit", "shift+q")]).is_err());
+
+        // analyzing is only possible after the game, when keys of colors do nothing
+        assert!(parse(&[("analyze", "1")]).is_ok());
+        assert!(parse(&[("quit", "ctrl+c esc")]).is_ok());
+        assert!(parse(&[("colors", "qwerty")]).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use std::convert::TryFrom;
+
+/// Margin on the left of the board
+const BOARD_INDENT: usize = 2;
+
+/// Margin between hints and codes
+const HINT_MARGIN: usize = 2;
+
+/// Margin between the board and the legend
+const LEGEND_MARGIN: usize = 2;
+
+/// Sizes of the parts of the board. Hints are wrapped onto more lines when
+/// a row does not fit in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardLayout {
+    holes: usize,
+    /// Number of lines a hint is wrapped into
+    hint_lines: usize,
+}
+
+impl BoardLayout {
+    /// Layout of rows with `holes` holes for a terminal `width` columns wide.
+    /// When the codes alone do not fit, wrapping hints does not help and
+    /// they are kept on a single line.
+    pub fn new(holes: usize, width: u16) -> Self {
+        let code_width = code_width(holes);
+        let hint_lines = (1..=holes)
+            .find(|&lines| {
+                let hint_width = holes.div_ceil(lines) + HINT_MARGIN;
+                BOARD_INDENT + hint_width + code_width + LEGEND_MARGIN <= width as usize
+            })
+            .unwrap_or(1);
+
+        Self { holes, hint_lines }
+    }
+
+    /// Number of key pegs on each line of a hint
+    pub fn hint_pegs_per_line(&self) -> usize {
+        self.holes.div_ceil(self.hint_lines)
+    }
+
+    /// Width of the hints including the margin on their right
+    pub fn hint_width(&self) -> u16 {
+        to_u16(self.hint_pegs_per_line() + HINT_MARGIN)
+    }
+
+    /// Width of the hints and codes including the margin on their right
+    pub fn board_width(&self) -> u16 {
+        to_u16(self.hint_pegs_per_line() + HINT_MARGIN + code_width(self.holes) + LEGEND_MARGIN)
+    }
+
+    pub fn indent(&self) -> u16 {
+        BOARD_INDENT as u16
+    }
+
+    /// Height of a row of a guess
+    pub fn row_height(&self) -> u16 {
+        to_u16(self.hint_lines)
+    }
+
+    /// Height of the board with the solution row at the top
+    pub fn board_height(&self, guesses: usize) -> u16 {
+        to_u16(guesses * self.hint_lines + 1)
+    }
+
+    /// Number of rows of guesses, up to `guesses`, which fit in `height`
+    /// lines along with the solution row
+    pub fn visible_rows(&self, guesses: usize, height: u16) -> usize {
+        let rows = (height as usize).saturating_sub(1) / self.hint_lines;
+        rows.min(guesses)
+    }
+}
+
+/// Code pegs separated by spaces
+fn code_width(holes: usize) -> usize {
+    (2 * holes).saturating_sub(1)
+}
+
+fn to_u16(n: usize) -> u16 {
+    u16::try_from(n).unwrap_or(u16::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_on_one_line() {
+        let layout = BoardLayout::new(4, 80);
+        assert_eq!(layout.row_height(), 1);
+        assert_eq!(layout.hint_width(), 6);
+        assert_eq!(layout.board_width(), 15);
+        assert_eq!(layout.board_height(8), 9);
+    }
+
+    #[test]
+    fn wraps_hints() {
+        // 2 + (30 + 2) + 59 + 2 = 95 columns on one line
+        assert_eq!(BoardLayout::new(30, 95).row_height(), 1);
+
+        let layout = BoardLayout::new(30, 94);
+        assert_eq!(layout.row_height(), 2);
+        assert_eq!(layout.hint_pegs_per_line(), 15);
+        assert_eq!(layout.board_height(8), 17);
+
+        assert_eq!(BoardLayout::new(30, 80).row_height(), 2);
+        assert_eq!(BoardLayout::new(30, 70).row_height(), 6);
+        assert_eq!(BoardLayout::new(30, 60).row_height(), 1);
+    }
+
+    #[test]
+    fn visible_rows() {
+        let layout = BoardLayout::new(4, 80);
+        assert_eq!(layout.visible_rows(8, 20), 8);
+        assert_eq!(layout.visible_rows(20, 9), 8);
+        assert_eq!(layout.visible_rows(20, 0), 0);
+
+        let layout = BoardLayout::new(30, 94);
+        assert_eq!(layout.visible_rows(20, 10), 4);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{data_path, Settings};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::Duration;
+
+static LEADERBOARD_FILE_NAME: &str = "leaderboard.json";
+
+/// Number of entries kept in the leaderboard
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Solving time at which the time does not affect the score
+const PAR_SECONDS: f64 = 60.0;
+
+/// Score of a won game. It grows with the number of possible codes, and
+/// shrinks with the number of guesses and the time taken.
+pub fn score(settings: &Settings, guesses: usize, elapsed: Duration) -> u64 {
+    // information needed to identify the solution, in bits
+    let colors = settings.code_colors();
+    let difficulty: f64 = (0..settings.holes.get())
+        .map(|i| {
+            if settings.no_duplicate {
+                colors.saturating_sub(i).max(1) as f64
+            } else {
+                colors as f64
+            }
+        })
+        .map(f64::log2)
+        .sum();
+
+    // up to twice as many points for solving faster than the par
+    let speed = 2.0 * PAR_SECONDS / (PAR_SECONDS + elapsed.as_secs_f64());
+
+    (1000.0 * difficulty / guesses.max(1) as f64 * speed).round() as u64
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub score: u64,
+    pub colors: usize,
+    pub holes: usize,
+    pub no_duplicate: bool,
+    #[serde(default)]
+    pub allow_empty: bool,
+    #[serde(default)]
+    pub blind: bool,
+    #[serde(default)]
+    pub static_hints: bool,
+    #[serde(default)]
+    pub hard: bool,
+    pub guesses: usize,
+    pub seconds: u64,
+}
+
+impl Entry {
+    pub fn new(name: &str, settings: &Settings, guesses: usize, elapsed: Duration) -> Self {
+        Self {
+            name: name.to_owned(),
+            score: score(settings, guesses, elapsed),
+            colors: settings.colors.get(),
+            holes: settings.holes.get(),
+            no_duplicate: settings.no_duplicate,
+            allow_empty: settings.allow_empty,
+            blind: settings.blind,
+            static_hints: settings.static_hints,
+            hard: settings.hard,
+            guesses,
+            seconds: elapsed.as_secs(),
+        }
+    }
+
+    /// e.g. "6 colors, 4 holes, no duplicate, hard"
+    pub fn configuration(&self) -> String {
+        let mut configuration = format!("{} colors, {} holes", self.colors, self.holes);
+        let variants = [
+            (self.no_duplicate, "no duplicate"),
+            (self.allow_empty, "allow empty"),
+            (self.blind, "blind"),
+            (self.static_hints, "static"),
+            (self.hard, "hard"),
+        ];
+        for (_, name) in variants.iter().filter(|(enabled, _)| *enabled) {
+            configuration += ", ";
+            configuration += name;
+        }
+        configuration
+    }
+}
+
+/// High scores in descending order
+#[derive(Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns the 0-based rank of the entry if it made it into the leaderboard
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        // among equal scores, older entries rank higher
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or(self.entries.len());
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+}
+
+pub fn load() -> Result<Leaderboard> {
+    load_from(&data_path(LEADERBOARD_FILE_NAME)?)
+}
+
+fn load_from(path: &Path) -> Result<Leaderboard> {
+    if !path.exists() {
+        return Ok(Leaderboard::default());
+    }
+
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let leaderboard = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    Ok(leaderboard)
+}
+
+pub fn store(leaderboard: &Leaderboard) -> Result<()> {
+    let path = data_path(LEADERBOARD_FILE_NAME)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let file =
+        File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), leaderboard)?;
+
+    Ok(())
+}
+
+/// Records a won game and returns its rank in the leaderboard
+pub fn update(entry: Entry) -> Result<Option<usize>> {
+    let mut leaderboard = load()?;
+    let rank = leaderboard.insert(entry);
+    if rank.is_some() {
+        store(&leaderboard)?;
+    }
+
+    Ok(rank)
+}
+
+/// Name of the player when it is given neither on the command line nor in
+/// the config file
+pub fn default_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "anonymous".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    fn settings(colors: usize, no_duplicate: bool) -> Settings {
+        Settings {
+            colors: NonZeroUsize::new(colors).unwrap(),
+            no_duplicate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn score_reflects_difficulty_and_performance() {
+        let minute = Duration::from_secs(60);
+        let base = score(&settings(6, false), 4, minute);
+
+        assert!(score(&settings(8, false), 4, minute) > base);
+        assert!(score(&settings(6, true), 4, minute) < base);
+        assert!(score(&settings(6, false), 5, minute) < base);
+        assert!(score(&settings(6, false), 4, Duration::from_secs(30)) > base);
+        assert!(score(&settings(6, false), 4, Duration::from_secs(0)) <= 2 * base);
+    }
+
+    #[test]
+    fn labeled_with_variants() {
+        let mut settings = settings(6, true);
+        settings.blind = true;
+        let entry = Entry::new("a", &settings, 4, Duration::from_secs(60));
+        assert_eq!(
+            entry.configuration(),
+            "6 colors, 4 holes, no duplicate, blind"
+        );
+    }
+
+    #[test]
+    fn keeps_highest_scores() {
+        let mut leaderboard = Leaderboard::default();
+        let entry = |name: &str, score| Entry {
+            name: name.to_owned(),
+            score,
+            colors: 6,
+            holes: 4,
+            no_duplicate: false,
+            allow_empty: false,
+            blind: false,
+            static_hints: false,
+            hard: false,
+            guesses: 4,
+            seconds: 60,
+        };
+
+        for i in 0..LEADERBOARD_SIZE as u64 {
+            assert_eq!(leaderboard.insert(entry("a", 100 + i)), Some(0));
+        }
+        assert_eq!(leaderboard.insert(entry("b", 100)), None);
+        assert_eq!(leaderboard.insert(entry("c", 105)), Some(5));
+
+        let entries = leaderboard.entries();
+        assert_eq!(entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(entries[0].score, 109);
+        assert_eq!(entries[5].name, "c");
+        assert_eq!(entries[LEADERBOARD_SIZE - 1].score, 101);
+    }
+}
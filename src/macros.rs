@@ -0,0 +1,165 @@
+use crate::keymap::KeyMap;
+use crate::Settings;
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Guess a macro fills the current row with
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// Numbers of the colors as typed
+    Colors(Vec<usize>),
+    /// The first colors, one in each hole
+    Distinct,
+}
+
+impl FromStr for Pattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "distinct" {
+            return Ok(Self::Distinct);
+        }
+
+        let colors = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|digit| digit as usize)
+                    .ok_or_else(|| anyhow::anyhow!("'{}' is not a number", c))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if colors.is_empty() {
+            return Err(anyhow::anyhow!("pattern is empty"));
+        }
+        Ok(Self::Colors(colors))
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Colors(numbers) => numbers
+                .iter()
+                .try_for_each(|number| write!(f, "{}", number)),
+            Self::Distinct => f.write_str("distinct colors"),
+        }
+    }
+}
+
+impl Pattern {
+    /// Colors of the guess under the settings, or the reason it cannot be
+    /// made
+    pub fn expand(&self, settings: &Settings) -> Result<Vec<usize>, String> {
+        let colors = settings.colors.get();
+        let holes = settings.holes.get();
+
+        match self {
+            Self::Colors(numbers) => {
+                if numbers.len() != holes {
+                    return Err(format!(
+                        "The macro has {} colors but the row has {} holes",
+                        numbers.len(),
+                        holes
+                    ));
+                }
+                let pattern = numbers
+                    .iter()
+                    .map(|&number| {
+                        settings.number_color(number).ok_or_else(|| {
+                            format!(
+                                "There is no color {}; choose from {}",
+                                number,
+                                settings.color_range()
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if settings.no_duplicate && pattern.iter().unique().count() < holes {
+                    return Err("The macro repeats colors; colors cannot duplicate".to_owned());
+                }
+                Ok(pattern)
+            }
+            Self::Distinct if holes > colors => Err(format!(
+                "There are not enough colors to fill {} holes without repetition",
+                holes
+            )),
+            Self::Distinct => Ok((0..holes).collect()),
+        }
+    }
+}
+
+/// Keys which fill the current row with a guess
+#[derive(Debug, Default)]
+pub struct Macros(BTreeMap<char, Pattern>);
+
+impl Macros {
+    /// Parses the `[macros]` table of the config. Keys bound to actions or
+    /// colors in `keys` cannot be used.
+    pub fn parse(definitions: &BTreeMap<String, String>, keys: &KeyMap) -> Result<Self> {
+        let mut macros = BTreeMap::new();
+        for (key, pattern) in definitions {
+            let mut chars = key.chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(anyhow::anyhow!("macro key '{}' is not a single key", key)),
+            };
+            keys.check_macro_key(c)
+                .map_err(|reason| anyhow::anyhow!("{} and cannot be used for a macro", reason))?;
+
+            let pattern = pattern
+                .parse()
+                .with_context(|| format!("invalid macro '{}'", key))?;
+            macros.insert(c, pattern);
+        }
+        Ok(Self(macros))
+    }
+
+    pub fn get(&self, key: char) -> Option<&Pattern> {
+        self.0.get(&key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, &Pattern)> {
+        self.0.iter().map(|(key, pattern)| (*key, pattern))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn expand_patterns() {
+        let definitions: BTreeMap<_, _> = [("a", "1122"), ("d", "distinct"), ("b", "1 2 7")]
+            .iter()
+            .map(|(key, pattern)| (key.to_string(), pattern.to_string()))
+            .collect();
+        let macros = Macros::parse(&definitions, &KeyMap::default()).unwrap();
+        let settings = Settings::default();
+
+        let expand = |key| macros.get(key).unwrap().expand(&settings);
+        assert_eq!(expand('a'), Ok(vec![0, 0, 1, 1]));
+        assert_eq!(expand('d'), Ok(vec![0, 1, 2, 3]));
+        assert!(expand('b').is_err());
+        assert!(macros.get('c').is_none());
+
+        let settings = Settings {
+            colors: NonZeroUsize::new(3).unwrap(),
+            ..Default::default()
+        };
+        assert!(Pattern::Distinct.expand(&settings).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_definitions() {
+        for (key, pattern) in &[("ab", "1122"), ("1", "1122"), ("q", "1122"), ("a", "12x4")] {
+            let definitions: BTreeMap<_, _> =
+                std::iter::once((key.to_string(), pattern.to_string())).collect();
+            assert!(Macros::parse(&definitions, &KeyMap::default()).is_err());
+        }
+    }
+}
@@ -1,33 +1,112 @@
-use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+mod animation;
+mod assist;
+mod batch;
+mod bench;
+mod config;
+mod counter;
+mod daily;
+mod events;
+mod explain;
+mod glyphs;
+mod help;
+mod hook;
+mod input;
+mod keymap;
+mod layout;
+mod leaderboard;
+mod macros;
+mod marathon;
+mod network;
+mod notification;
+mod plain;
+mod replay;
+mod review;
+mod ruleset;
+mod save;
+mod share;
+mod solver;
+mod stats;
+mod stopwatch;
+mod suspend;
+mod theme;
+mod title;
+mod vectors;
+mod word;
+
+use animation::Animations;
+use anyhow::{Context, Result};
+use assist::Assistant;
+use counter::CandidateCounter;
+use crossbeam_channel::Receiver;
+use crossterm::event::{self, Event, KeyCode};
 use crossterm::terminal;
+use events::GameEvent;
+use glyphs::Glyphs;
+use hook::{Hook, Summary};
+use input::{ColorInput, InputMode};
 use itertools::{izip, Itertools};
+use keymap::{KeyAction, KeyMap};
+use layout::BoardLayout;
+use leaderboard::Entry;
+use macros::Macros;
+use marathon::Marathon;
+use network::Connection;
+use notification::Notifications;
 use rand::prelude::*;
+use replay::{Action, Replay};
+use review::{Rating, Reviewer};
+use ruleset::Ruleset;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::io::{self, Write};
 use std::iter;
-use std::num::NonZeroUsize;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use stopwatch::{format_duration, Stopwatch};
 use structopt::StructOpt;
+use suspend::Signal;
+use theme::{Theme, ThemeConfig};
+use title::TerminalTitle;
 use tui::backend::CrosstermBackend;
-use tui::layout::{Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Style};
-use tui::widgets::{Paragraph, Text};
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::widgets::{Clear, Paragraph, Text};
 use tui::Frame;
 use tui::Terminal;
 
-static CIRCLE: &str = "●";
-static DOT: &str = "∙";
-
-static CODE_COLORS: &[Color] = &[
-    Color::Blue,
-    Color::Red,
-    Color::Green,
-    Color::Yellow,
-    Color::Magenta,
-    Color::White,
-    Color::Cyan,
-];
-static BULL_COLOR: Color = Color::Red;
-static COW_COLOR: Color = Color::White;
+/// Colors are selected with number keys 1-9
+const MAX_COLORS: usize = 9;
+
+/// Number of colors in the numeric variant, the digits 0-9
+const DIGITS: usize = 10;
+
+/// Number of guesses after which the explanation of bulls & cows collapses
+const LEGEND_AUTO_HIDE_GUESSES: usize = 3;
+
+/// Width of the explanation of bulls & cows
+const LEGEND_WIDTH: u16 = 36;
+
+/// Rows of guesses shown at least when the board is taller than the
+/// terminal
+const MIN_VISIBLE_ROWS: usize = 3;
+
+/// Lines below the board while playing: instructions, the number of pegs
+/// placed, how to undo, a margin, and a notification
+const FOOTER_HEIGHT: u16 = 5;
+
+/// Interval of redrawing the clocks
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Random rows tried for one which agrees with the hints under --hard when
+/// the turn time runs out
+const COMPLETION_ATTEMPTS: usize = 100;
+
+/// Time left in a turn from which the countdown stands out
+const TURN_TIME_WARNING: Duration = Duration::from_secs(5);
+
+/// Width of the countdown of a turn, e.g. "0:30"
+const TURN_TIME_WIDTH: u16 = 5;
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -39,6 +118,195 @@ static COW_COLOR: Color = Color::White;
     setting(clap::AppSettings::AllArgsOverrideSelf)
 )]
 struct Opt {
+    #[structopt(flatten)]
+    settings: Settings,
+
+    /// Save the game to the file on Ctrl+S or when quitting
+    #[structopt(long, value_name = "path")]
+    save: Option<PathBuf>,
+
+    /// Record the game to the file to play it back with the replay subcommand
+    #[structopt(long, value_name = "path")]
+    record: Option<PathBuf>,
+
+    /// Resume the game saved in the file
+    #[structopt(long, value_name = "path", conflicts_with_all = SETTINGS_ARGS)]
+    resume: Option<PathBuf>,
+
+    /// Play with the rule set in the file
+    #[structopt(
+        long,
+        value_name = "path",
+        conflicts_with = "resume",
+        conflicts_with_all = SETTINGS_ARGS
+    )]
+    ruleset: Option<PathBuf>,
+
+    /// Color theme: default, bright, light, or one defined in the config file
+    #[structopt(long, value_name = "name")]
+    theme: Option<String>,
+
+    /// Draw code pegs as numbers and key pegs as distinct shapes
+    #[structopt(long)]
+    symbols: bool,
+
+    /// Draw the board with ASCII characters only, for terminals without Unicode
+    #[structopt(long)]
+    ascii: bool,
+
+    /// Show hints and the solution at once instead of peg by peg
+    #[structopt(long)]
+    no_animations: bool,
+
+    /// Type colors as their initials or names besides the number keys
+    #[structopt(long, value_name = "mode", possible_values = input::MODES)]
+    input: Option<InputMode>,
+
+    /// Show suggestions of next guesses
+    #[structopt(long)]
+    assist: bool,
+
+    /// Maximum number of threads the solver runs on [default: number of CPUs]
+    #[structopt(long, value_name = "n")]
+    threads: Option<NonZeroUsize>,
+
+    /// Name of the player on the leaderboard
+    #[structopt(long, value_name = "name")]
+    name: Option<String>,
+
+    /// Read guesses from stdin and write hints to stdout without the TUI
+    #[structopt(long)]
+    batch: bool,
+
+    /// Play with a line-based prompt instead of the TUI, e.g. with a screen reader
+    #[structopt(long, conflicts_with_all = &["batch", "json", "record", "assist", "marathon"])]
+    no_tui: bool,
+
+    /// Do not show the progress in the terminal title
+    #[structopt(long)]
+    no_title: bool,
+
+    /// Write game events to stdout as JSON lines, and read commands as JSON lines with --batch
+    #[structopt(long)]
+    json: bool,
+
+    /// Play the puzzle of the day, which is the same for everyone with the same rules
+    #[structopt(long, conflicts_with = "resume")]
+    daily: bool,
+
+    /// Print the hints of the guesses without the colors, to share the result
+    #[structopt(long, conflicts_with = "batch")]
+    share: bool,
+
+    /// Start the next code as soon as one is broken, until one is not
+    #[structopt(
+        long,
+        conflicts_with_all = &["daily", "save", "resume", "record", "batch"]
+    )]
+    marathon: bool,
+
+    /// Write the rules given by the options, the theme and the keys to the file instead of playing
+    #[structopt(long, value_name = "path", conflicts_with_all = &["resume", "ruleset"])]
+    export_ruleset: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Show statistics of played games
+    Stats,
+    /// Show the high scores
+    Leaderboard,
+    /// Make a code for another player to break over the network
+    Host {
+        /// Port to listen on
+        #[structopt(long, default_value = "4321")]
+        port: u16,
+    },
+    /// Break the code made by another player over the network
+    Join {
+        /// Address of the host, e.g. 192.168.0.2:4321
+        #[structopt(value_name = "address")]
+        address: String,
+    },
+    /// Play back a game recorded with --record
+    Replay {
+        #[structopt(value_name = "path")]
+        path: PathBuf,
+        /// Playback speed
+        #[structopt(long, default_value = "1")]
+        speed: f64,
+    },
+    /// Guess a word, with each letter marked as in Wordle
+    Word {
+        /// Number of letters
+        #[structopt(long, default_value = "5")]
+        length: NonZeroUsize,
+        /// File with one word per line [default: /usr/share/dict/words]
+        #[structopt(long, value_name = "path")]
+        wordlist: Option<PathBuf>,
+    },
+    /// Measure how many guesses a solving strategy takes to break the codes
+    Bench {
+        /// Solving strategy
+        #[structopt(long, default_value = "entropy", possible_values = bench::STRATEGIES)]
+        strategy: bench::Strategy,
+        /// Break this many random codes instead of every code
+        #[structopt(long, value_name = "count")]
+        sample: Option<usize>,
+        /// Seed of the random codes and the random strategy
+        #[structopt(long, value_name = "seed")]
+        seed: Option<u64>,
+    },
+    /// Write the hints of pairs of codes under the rules, to test other implementations
+    Vectors {
+        /// Write this many random pairs instead of every pair
+        #[structopt(long, value_name = "count")]
+        sample: Option<usize>,
+        /// Seed of the random pairs
+        #[structopt(long, value_name = "seed")]
+        seed: Option<u64>,
+    },
+    /// Show the path of the config file
+    Config {
+        /// Write a commented template to the config file
+        #[structopt(long)]
+        init: bool,
+    },
+}
+
+/// What to do with the game once it is set up
+enum Mode {
+    Play,
+    Replay(Replay, f64),
+    Host(u16),
+    Join(String),
+}
+
+/// Arguments which make up `Settings`
+const SETTINGS_ARGS: &[&str] = &[
+    "colors",
+    "guesses",
+    "holes",
+    "no-duplicate",
+    "hard",
+    "time-limit",
+    "turn-time",
+    "forfeit-turn",
+    "digits",
+    "blind",
+    "static",
+    "allow-empty",
+];
+
+// only for rustdoc, as structopt would show a doc comment as the about
+// text of the app into which this is flattened
+#[cfg_attr(doc, doc = "Rules of a game")]
+#[derive(Debug, Clone, PartialEq, StructOpt, Serialize, Deserialize)]
+#[structopt(rename_all = "kebab-case")]
+struct Settings {
     /// Number of colors
     #[structopt(short, long, default_value = "6")]
     colors: NonZeroUsize,
@@ -54,31 +322,436 @@ struct Opt {
     /// Forbid colors to duplicate
     #[structopt(long)]
     no_duplicate: bool,
+
+    /// Reject guesses which contradict the hints so far
+    #[structopt(long, visible_alias = "strict")]
+    #[serde(default, alias = "strict")]
+    hard: bool,
+
+    /// Lose the game when the time runs out
+    #[structopt(long, value_name = "secs")]
+    #[serde(default)]
+    time_limit: Option<NonZeroU64>,
+
+    /// Fill the rest of the row at random and make the guess when a turn
+    /// takes longer than this
+    #[structopt(long, value_name = "secs")]
+    #[serde(default)]
+    turn_time: Option<NonZeroU64>,
+
+    /// Lose the turn instead of filling the row when the turn time runs out
+    #[structopt(long, requires = "turn-time")]
+    #[serde(default)]
+    forfeit_turn: bool,
+
+    /// Break a number of distinct digits 0-9 instead of colors
+    #[structopt(long, conflicts_with_all = &["colors", "no-duplicate"])]
+    #[serde(default)]
+    digits: bool,
+
+    /// Show only how many colors of each guess are correct, not whether they
+    /// are in the right positions
+    #[structopt(long)]
+    #[serde(default)]
+    blind: bool,
+
+    /// Hide the hints until all guesses but the last are made
+    #[structopt(long = "static")]
+    #[serde(default, rename = "static")]
+    static_hints: bool,
+
+    /// Let holes of the code be blank, typed as 0
+    #[structopt(long, conflicts_with = "digits")]
+    #[serde(default)]
+    allow_empty: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::from_iter([env!("CARGO_PKG_NAME")])
+    }
+}
+
+impl Settings {
+    /// Fixes the rules implied by the variant
+    fn resolve_variant(&mut self) {
+        if self.digits {
+            self.colors = NonZeroUsize::new(DIGITS).unwrap();
+            self.no_duplicate = true;
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.digits {
+            if self.colors.get() != DIGITS || !self.no_duplicate {
+                return Err(anyhow::anyhow!(
+                    "--digits plays with {} colors without duplicates",
+                    DIGITS
+                ));
+            }
+        } else if self.colors.get() > MAX_COLORS {
+            return Err(anyhow::anyhow!("--colors must be <= {}", MAX_COLORS));
+        }
+        if self.digits && self.allow_empty {
+            return Err(anyhow::anyhow!(
+                "--digits cannot be used with --allow-empty"
+            ));
+        }
+        if self.no_duplicate && self.holes.get() > self.code_colors() {
+            return Err(anyhow::anyhow!(
+                "--colors must be >= --holes when --no-duplicate"
+            ));
+        }
+        if self.forfeit_turn && self.turn_time.is_none() {
+            return Err(anyhow::anyhow!("--forfeit-turn needs --turn-time"));
+        }
+        if self.hard && (self.blind || self.static_hints) {
+            return Err(anyhow::anyhow!(
+                "--hard cannot be used with --blind or --static"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Number of colors a hole of the code can take, counting the blank
+    fn code_colors(&self) -> usize {
+        self.colors.get() + self.allow_empty as usize
+    }
+
+    /// Color standing for an empty hole, which comes after the real colors
+    fn blank(&self) -> Option<usize> {
+        if self.allow_empty {
+            Some(self.colors.get())
+        } else {
+            None
+        }
+    }
+
+    /// Number a color is typed and shown as
+    fn color_number(&self, color: usize) -> usize {
+        if self.blank() == Some(color) {
+            0
+        } else if self.digits {
+            color
+        } else {
+            color + 1
+        }
+    }
+
+    /// Color typed as the number, if there is one
+    fn number_color(&self, number: usize) -> Option<usize> {
+        let color = if self.digits {
+            number
+        } else if number == 0 {
+            return self.blank();
+        } else {
+            number - 1
+        };
+        Some(color).filter(|&color| color < self.colors.get())
+    }
+
+    /// Numbers of the colors, e.g. "1-6", or "0-6" with the blank
+    fn color_range(&self) -> String {
+        format!(
+            "{}-{}",
+            if self.allow_empty {
+                0
+            } else {
+                self.color_number(0)
+            },
+            self.color_number(self.colors.get() - 1)
+        )
+    }
 }
 
 fn main() -> Result<()> {
-    let opt = Opt::from_args();
+    let matches = Opt::clap().get_matches();
+    let mut opt = Opt::from_clap(&matches);
+    if let Some(threads) = opt.threads {
+        solver::set_threads(threads.get());
+    }
+
+    let mode = match opt.command.take() {
+        Some(Command::Stats) => return print_stats(),
+        Some(Command::Leaderboard) => return print_leaderboard(),
+        Some(Command::Config { init }) => {
+            if init {
+                let path = config::init()?;
+                eprintln!("Wrote {}", path.display());
+            } else {
+                println!("{}", config::path()?.display());
+            }
+            return Ok(());
+        }
+        Some(Command::Word { length, wordlist }) => {
+            let mut glyphs = Glyphs::default();
+            if opt.ascii || config::load()?.ascii().unwrap_or(false) {
+                glyphs = glyphs.ascii();
+            }
+            let settings = rules(opt, &matches)?;
+            let path = wordlist.unwrap_or_else(|| PathBuf::from(word::DEFAULT_WORDLIST));
+            let words = word::load(&path, length.get())?;
+            return word::play(words, settings.guesses.get(), glyphs.empty);
+        }
+        Some(Command::Bench {
+            strategy,
+            sample,
+            seed,
+        }) => {
+            let settings = rules(opt, &matches)?;
+            let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+            println!("{}", bench::run(&settings, strategy, sample, seed)?);
+            return Ok(());
+        }
+        Some(Command::Vectors { sample, seed }) => {
+            let settings = rules(opt, &matches)?;
+            let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+            let stdout = io::stdout();
+            return vectors::export(&settings, sample, seed, io::BufWriter::new(stdout.lock()));
+        }
+        Some(Command::Replay { path, speed }) => Mode::Replay(replay::load(&path)?, speed),
+        Some(Command::Host { port }) => Mode::Host(port),
+        Some(Command::Join { address }) => Mode::Join(address),
+        None => Mode::Play,
+    };
+
+    let is_network_game = matches!(mode, Mode::Host(_) | Mode::Join(_));
+    if is_network_game
+        && (opt.save.is_some()
+            || opt.resume.is_some()
+            || opt.record.is_some()
+            || opt.daily
+            || opt.batch
+            || opt.marathon
+            || opt.no_tui)
+    {
+        return Err(anyhow::anyhow!(
+            "--save, --resume, --record, --daily, --batch, --marathon and --no-tui cannot be used in network games"
+        ));
+    }
 
-    if opt.colors.get() > CODE_COLORS.len() {
-        return Err(anyhow::anyhow!("--colors must be <= {}", CODE_COLORS.len()));
+    let config = config::load()?;
+    config.apply(&mut opt.settings, &matches);
+    opt.settings.resolve_variant();
+
+    if let Some(path) = &opt.export_ruleset {
+        opt.settings.validate()?;
+        let theme = opt
+            .theme
+            .as_deref()
+            .or_else(|| config.theme())
+            .map(|name| ThemeConfig::named(name, config.themes()))
+            .transpose()?;
+        let ruleset = Ruleset::new(path, opt.settings, theme, config.key_definitions().clone());
+        ruleset::store(path, &ruleset)?;
+        eprintln!(
+            "Exported rule set \"{}\" to {}",
+            ruleset.name,
+            path.display()
+        );
+        return Ok(());
     }
-    if opt.no_duplicate && opt.holes > opt.colors {
+
+    let ruleset = opt.ruleset.as_deref().map(ruleset::load).transpose()?;
+    let mut game = if let Mode::Replay(replay, _) = &mode {
+        replay.game()
+    } else if let Mode::Join(address) = &mode {
+        network::join(address)?
+    } else if let Some(path) = &opt.resume {
+        save::load(path)?
+    } else {
+        let (settings, ruleset_name) = if let Some(ruleset) = &ruleset {
+            (ruleset.settings.clone(), Some(ruleset.name.clone()))
+        } else {
+            opt.settings.validate()?;
+            (opt.settings, None)
+        };
+
+        let mut game = if opt.daily {
+            Game::daily(settings)?
+        } else {
+            Game::new(settings)
+        };
+        game.ruleset_name = ruleset_name;
+        game
+    };
+    let ruleset_theme = ruleset
+        .as_ref()
+        .and_then(|ruleset| Some((ruleset.name.as_str(), ruleset.theme.as_ref()?)));
+    let theme_name = match (opt.theme.as_deref(), ruleset_theme) {
+        (None, Some((ruleset_name, theme))) => {
+            game.theme = theme.build()?;
+            format!("the theme of rule set \"{}\"", ruleset_name)
+        }
+        (name, _) => {
+            let name = name.or_else(|| config.theme()).unwrap_or("default");
+            game.theme = Theme::resolve(name, config.themes())?;
+            format!("theme \"{}\"", name)
+        }
+    };
+    if !game.settings.digits && game.settings.colors.get() > game.theme.pegs.len() {
         return Err(anyhow::anyhow!(
-            "--colors must be >= --holes when --no-duplicate"
+            "--colors must be <= {} with {}",
+            game.theme.pegs.len(),
+            theme_name
         ));
     }
 
-    Game::new(&opt).run()?;
+    if game.settings.digits {
+        game.glyphs = Glyphs::digits();
+    } else if opt.symbols {
+        game.glyphs = Glyphs::symbols();
+    }
+    if opt.ascii || config.ascii().unwrap_or(false) {
+        game.glyphs = game.glyphs.ascii();
+    }
+    game.animations = Animations::new(!opt.no_animations);
+
+    if let Some(name) = opt.name.or_else(|| config.name().map(str::to_owned)) {
+        game.player = name;
+    }
+
+    game.keys = config.keys()?;
+    if let Some(ruleset) = &ruleset {
+        game.keys = config
+            .keys_with(&ruleset.keys)
+            .with_context(|| format!("invalid keys in rule set \"{}\"", ruleset.name))?;
+    }
+
+    match mode {
+        Mode::Replay(replay, speed) => return replay::play(&mut game, &replay, speed),
+        Mode::Host(port) => return network::host(&mut game, port),
+        Mode::Play | Mode::Join(_) => (),
+    }
+
+    if opt.batch {
+        if game.settings.blind || game.settings.static_hints {
+            return Err(anyhow::anyhow!(
+                "--blind and --static cannot be used with --batch"
+            ));
+        }
+        let stdin = io::stdin();
+        return batch::play(&mut game, stdin.lock(), io::stdout(), opt.json);
+    }
+
+    game.hook = config.hook();
+    game.macros = config.macros(&game.keys)?;
+    game.set_input_mode(opt.input.or_else(|| config.input()).unwrap_or_default());
+    game.json = opt.json;
+    game.print_share = opt.share;
+    game.suggest_difficulty = config.suggest_difficulty().unwrap_or(true);
+    if !opt.no_title && config.title().unwrap_or(true) {
+        game.title = Some(TerminalTitle::new());
+    }
+    game.save_path = opt.save.or(opt.resume);
+    if let Some(path) = opt.record {
+        game.recording = Some(Replay::new(&game));
+        game.record_path = Some(path);
+    }
+    if opt.assist {
+        if game.settings.blind || game.settings.static_hints {
+            return Err(anyhow::anyhow!(
+                "--assist cannot be used with --blind or --static"
+            ));
+        }
+        game.enable_assist();
+    }
+    if opt.no_tui {
+        let stdin = io::stdin();
+        return plain::play(&mut game, stdin.lock(), io::stdout());
+    }
+    if opt.marathon {
+        let best = stats::load()?
+            .get(&game.settings)
+            .map_or(0, |record| record.best_streak);
+        game.marathon = Some(Marathon::new(best));
+    }
+    game.run()?;
+
+    Ok(())
+}
+
+/// Rules given by --ruleset, or by the options and the config file
+fn rules(mut opt: Opt, matches: &clap::ArgMatches) -> Result<Settings> {
+    if let Some(path) = &opt.ruleset {
+        return Ok(ruleset::load(path)?.settings);
+    }
+
+    config::load()?.apply(&mut opt.settings, matches);
+    opt.settings.resolve_variant();
+    opt.settings.validate()?;
+    Ok(opt.settings)
+}
+
+fn print_stats() -> Result<()> {
+    let stats = stats::load()?;
+    if stats.records().is_empty() {
+        println!("No games played yet");
+        return Ok(());
+    }
+
+    for (i, record) in stats.records().iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", record.configuration());
+        println!("{}", record.summary());
+        for line in record.histogram(0, Glyphs::default().bar) {
+            println!("{}", line);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_leaderboard() -> Result<()> {
+    let leaderboard = leaderboard::load()?;
+    if leaderboard.entries().is_empty() {
+        println!("No games won yet");
+        return Ok(());
+    }
+
+    let name_width = leaderboard
+        .entries()
+        .iter()
+        .map(|entry| entry.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Name".len());
+    println!(
+        " #  {:name_width$}  Score  Guesses   Time  Rules",
+        "Name",
+        name_width = name_width
+    );
+    for (i, entry) in leaderboard.entries().iter().enumerate() {
+        println!(
+            "{:>2}  {:name_width$}  {:>5}  {:>7}  {:>5}  {}",
+            i + 1,
+            entry.name,
+            entry.score,
+            entry.guesses,
+            format_duration(Duration::from_secs(entry.seconds)),
+            entry.configuration(),
+            name_width = name_width
+        );
+    }
 
     Ok(())
 }
 
+/// Path to a file in the platform data directory
+fn data_path(file_name: &str) -> Result<PathBuf> {
+    let dir = dirs::data_dir().ok_or_else(|| anyhow::anyhow!("data directory not found"))?;
+    Ok(dir.join(env!("CARGO_PKG_NAME")).join(file_name))
+}
+
 type Backend = CrosstermBackend<io::Stderr>;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 struct Guess(Vec<usize>);
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 struct Hint {
     /// Correct color, correct position
     bulls: usize,
@@ -93,219 +766,1659 @@ enum State {
     Lost,
 }
 
-struct Game<'a> {
-    opt: &'a Opt,
+/// Action waiting for the player to confirm it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Confirm {
+    GiveUp,
+    NewGame,
+}
+
+impl Confirm {
+    fn prompt(self) -> &'static str {
+        match self {
+            Self::GiveUp => "Give up and reveal the code? (y/n)",
+            Self::NewGame => "Abandon this game and start a new one? (y/n)",
+        }
+    }
+}
+
+struct Game {
+    settings: Settings,
+    /// Seed of the RNG the solution was drawn from
+    seed: u64,
     solution: Guess,
     guesses: Vec<Guess>,
     hints: Vec<Hint>,
     current_guess: Guess,
+    /// Hole of the current row the next color goes in, or `None` to follow
+    /// the end of the row
+    cursor: Option<usize>,
+    /// Whether a color placed at the cursor pushes the following pegs right
+    /// instead of replacing the one there
+    insert_mode: bool,
+    /// `None` collapses the legend automatically once the game is under way
+    show_legend: Option<bool>,
+    save_path: Option<PathBuf>,
+    notifications: Notifications,
+    ruleset_name: Option<String>,
+    /// Statistics of the configuration, available once the game is over
+    stats: Option<stats::Record>,
+    /// Day of the daily puzzle being played
+    daily: Option<u64>,
+    assistant: Option<Assistant>,
+    counter: CandidateCounter,
+    keys: KeyMap,
+    macros: Macros,
+    /// Whether to show the most likely colors in the solution row
+    show_overlay: bool,
+    theme: Theme,
+    glyphs: Glyphs,
+    /// Time spent playing
+    stopwatch: Stopwatch,
+    /// Time on the stopwatch when the current turn started
+    turn_started: Duration,
+    /// Name of the player on the leaderboard
+    player: String,
+    /// Score and rank in the leaderboard, available once the game is won
+    score: Option<(u64, Option<usize>)>,
+    record_path: Option<PathBuf>,
+    recording: Option<Replay>,
+    /// Line shown instead of the instructions, e.g. while playing back a replay
+    caption: Option<String>,
+    /// Command to run when the game finishes
+    hook: Option<Hook>,
+    /// Whether to write events to stdout as JSON lines
+    json: bool,
+    title: Option<TerminalTitle>,
+    /// Whether to suggest another configuration based on the statistics
+    suggest_difficulty: bool,
+    /// Harder or easier configuration to try, available once the game is over
+    suggestion: Option<String>,
+    /// Host of a network game, who gives the hints
+    remote: Option<Connection>,
+    /// Guess sent to the host and waiting for the hint
+    pending_guess: Option<Guess>,
+    /// Whether to show the solution while playing, e.g. to the host
+    show_solution: bool,
+    /// Whether the game is over and the final screen is kept until the
+    /// player leaves
+    post_game: bool,
+    /// Analysis of the guesses, started when first shown
+    reviewer: Option<Reviewer>,
+    show_review: bool,
+    confirm: Option<Confirm>,
+    /// Whether the player gave up, which loses the game
+    gave_up: bool,
+    /// Lowest row shown when the board does not fit in the terminal, or
+    /// `None` to follow the latest guess
+    board_scroll: Option<usize>,
+    show_help: bool,
+    /// Whether the board is hidden and the clock stopped until a key is
+    /// pressed
+    paused: bool,
+    input: ColorInput,
+    marathon: Option<Marathon>,
+    /// Whether to print the card of the result when the game is left
+    print_share: bool,
+    animations: Animations,
 }
 
-impl<'a> Game<'a> {
-    fn new(opt: &'a Opt) -> Game<'a> {
-        let mut rng = rand::thread_rng();
-        let solution = if opt.no_duplicate {
-            // sample without replacement
-            let mut colors = (0..opt.colors.get()).choose_multiple(&mut rng, opt.holes.get());
+impl Game {
+    fn new(settings: Settings) -> Self {
+        Self::with_seed(settings, rand::thread_rng().gen())
+    }
 
-            // choose_multiple doesn't shuffle, so we have to shuffle the result
-            colors.shuffle(&mut rng);
-            colors
-        } else {
-            use rand::distributions::Uniform;
+    /// Puzzle of the day, showing the previous result if it has already
+    /// been played
+    fn daily(settings: Settings) -> Result<Self> {
+        let day = daily::today();
+        let mut game = Self::with_seed(settings.clone(), daily::seed(day, &settings));
+        game.daily = Some(day);
+
+        if let Some(entry) = daily::find(day, &settings)? {
+            game.restore_daily(entry);
+            game.stats = stats::load()?.get(&game.settings).cloned();
+            game.notifications.push(format!(
+                "You have already played Daily #{}",
+                daily::puzzle_number(day)
+            ));
+        }
 
-            // sample with replacement
-            let dist = Uniform::new(0, opt.colors.get());
-            rng.sample_iter(dist).take(opt.holes.get()).collect()
-        };
+        Ok(game)
+    }
+
+    /// Shows the finished game as it ended, under the rules it was played
+    /// with
+    fn restore_daily(&mut self, entry: daily::Entry) {
+        let code_colors = entry.settings.code_colors();
+        self.hints = entry
+            .guesses
+            .iter()
+            .map(|guess| calc_hint(guess, &self.solution, code_colors))
+            .collect();
+        self.settings = entry.settings;
+        self.guesses = entry.guesses;
+        self.gave_up = entry.gave_up;
+        self.stopwatch =
+            Stopwatch::with_elapsed(Duration::try_from_secs_f64(entry.elapsed).unwrap_or_default());
+
+        // recorded before the outcome was kept, and lost some other way
+        if self.status() == State::Playing {
+            self.gave_up = true;
+        }
+    }
+
+    fn with_seed(settings: Settings, seed: u64) -> Self {
+        let solution = generate_solution(&settings, seed);
 
         Self {
-            opt,
-            solution: Guess(solution),
+            settings,
+            seed,
+            solution,
             guesses: Vec::new(),
             hints: Vec::new(),
             current_guess: Guess(Vec::new()),
+            cursor: None,
+            insert_mode: false,
+            show_legend: None,
+            save_path: None,
+            notifications: Notifications::default(),
+            ruleset_name: None,
+            stats: None,
+            daily: None,
+            assistant: None,
+            counter: CandidateCounter::default(),
+            keys: KeyMap::default(),
+            macros: Macros::default(),
+            show_overlay: true,
+            theme: Theme::default(),
+            glyphs: Glyphs::default(),
+            stopwatch: Stopwatch::default(),
+            turn_started: Duration::default(),
+            player: leaderboard::default_name(),
+            score: None,
+            record_path: None,
+            recording: None,
+            caption: None,
+            hook: None,
+            json: false,
+            title: None,
+            suggest_difficulty: true,
+            suggestion: None,
+            remote: None,
+            pending_guess: None,
+            show_solution: false,
+            post_game: false,
+            reviewer: None,
+            show_review: false,
+            confirm: None,
+            gave_up: false,
+            board_scroll: None,
+            show_help: false,
+            paused: false,
+            input: ColorInput::default(),
+            marathon: None,
+            print_share: false,
+            animations: Animations::default(),
         }
     }
 
+    fn enable_assist(&mut self) {
+        let mut assistant = Assistant::new(&self.settings);
+        assistant.refresh(&self.settings, &self.guesses, &self.hints);
+        self.assistant = Some(assistant);
+    }
+
     fn run(&mut self) -> Result<()> {
-        let (tx, rx) = crossbeam_channel::unbounded();
-        std::thread::spawn(move || loop {
-            if let Ok(event) = event::read() {
-                let _ = tx.send(event);
+        let rx = spawn_input_thread();
+        let mut terminal = setup_terminal()?;
+        if let Some(title) = &mut self.title {
+            title.enter(&mut io::stderr())?;
+        }
+
+        self.begin();
+
+        let signals = suspend::signals()?;
+        let ticker = crossbeam_channel::tick(TICK_INTERVAL);
+        let mut finished = false;
+        let mut connection_lost = false;
+        loop {
+            while self.status() == State::Playing {
+                let timeout = match self.notifications.update(Instant::now()) {
+                    Some(duration) => crossbeam_channel::after(duration),
+                    None => crossbeam_channel::never(),
+                };
+                let frame = self.animation_frame();
+
+                terminal.draw(|mut f| {
+                    self.draw(&mut f);
+                })?;
+                self.update_title()?;
+
+                let analysis_rx = match &self.assistant {
+                    Some(assistant) => assistant.receiver().clone(),
+                    None => crossbeam_channel::never(),
+                };
+                let remote_rx = match &self.remote {
+                    Some(remote) => remote.receiver().clone(),
+                    None => crossbeam_channel::never(),
+                };
+                let hook_rx = self.hook_failures();
+                let event = crossbeam_channel::select! {
+                    recv(rx) -> event => Some(event?),
+                    recv(hook_rx) -> err => {
+                        if let Ok(err) = err {
+                            self.on_hook_failure(err);
+                        }
+                        None
+                    }
+                    recv(analysis_rx) -> analysis => {
+                        if let (Ok(analysis), Some(assistant)) = (analysis, &mut self.assistant) {
+                            assistant.on_analysis(analysis, self.guesses.len());
+                        }
+                        None
+                    }
+                    recv(remote_rx) -> message => {
+                        let result = message?.and_then(|message| network::on_message(self, message));
+                        if let Err(err) = result {
+                            self.notifications.push(format!("Connection lost: {}", err));
+                            connection_lost = true;
+                        }
+                        None
+                    }
+                    recv(signals) -> signal => {
+                        self.on_signal(&mut terminal, signal?)?;
+                        None
+                    }
+                    recv(timeout) -> _ => None,
+                    recv(ticker) -> _ => None,
+                    recv(frame) -> _ => None,
+                };
+                if connection_lost {
+                    break;
+                }
+
+                if let (Some(Event::Key(key)), true) = (event, self.paused) {
+                    // any key resumes, and quitting leaves the board as usual
+                    self.resume();
+                    if self.keys.action(key, false) == Some(KeyAction::Quit) {
+                        break;
+                    }
+                } else if let (Some(Event::Key(_)), true) = (event, self.show_help) {
+                    // any key closes the help
+                    self.show_help = false;
+                } else if let (Some(Event::Key(key)), Some(confirm)) = (event, self.confirm) {
+                    self.confirm = None;
+                    // any other key cancels
+                    if let KeyCode::Char('y') | KeyCode::Char('Y') = key.code {
+                        self.on_confirm(confirm);
+                    }
+                } else if let Some(Event::Key(key)) =
+                    event.filter(|event| matches!(event, Event::Key(key) if self.input.takes(*key)))
+                {
+                    if let Some(color) = self.input.on_key(key) {
+                        self.place(color);
+                    }
+                } else if let Some(Event::Key(key)) = event {
+                    match self.keys.action(key, false) {
+                        Some(KeyAction::Quit) => break,
+                        Some(KeyAction::Undo) => self.on_backspace(),
+                        Some(KeyAction::Delete) => self.on_delete(),
+                        Some(KeyAction::CursorLeft) => self.move_cursor(true),
+                        Some(KeyAction::CursorRight) => self.move_cursor(false),
+                        Some(KeyAction::Insert) => self.toggle_insert(),
+                        Some(KeyAction::Save) => self.save(),
+                        Some(KeyAction::Pause) => self.pause(),
+                        Some(KeyAction::Suspend) => self.suspend(&mut terminal)?,
+                        Some(KeyAction::Submit) => self.on_enter(),
+                        Some(KeyAction::Legend) => self.toggle_legend(),
+                        Some(KeyAction::ScrollUp) => self.scroll_board(terminal.size()?, true),
+                        Some(KeyAction::ScrollDown) => self.scroll_board(terminal.size()?, false),
+                        Some(KeyAction::Help) => self.show_help = true,
+                        Some(KeyAction::GiveUp) => self.ask(Confirm::GiveUp),
+                        Some(KeyAction::NewGame) => self.on_new_game(),
+                        Some(KeyAction::Overlay) if self.assistant.is_some() => {
+                            self.show_overlay = !self.show_overlay
+                        }
+                        Some(_) => (),
+                        None => match key.code {
+                            KeyCode::Char(c) if self.macros.get(c).is_some() => self.run_macro(c),
+                            KeyCode::Char(c) => self.on_char(c),
+                            _ => (),
+                        },
+                    }
+                }
+
+                self.check_turn_time();
+
+                if self.status() != State::Playing {
+                    self.on_game_over();
+                    self.emit(&GameEvent::GameOver(Summary::new(self)));
+                    if self.next_round() {
+                        self.spawn_hook();
+                    } else {
+                        finished = true;
+                    }
+                }
+            }
+
+            if !finished || !self.linger(&mut terminal, &rx, &signals)? {
+                break;
+            }
+            self.spawn_hook();
+            self.restart();
+            finished = false;
+        }
+        self.close(terminal)?;
+        if let Some(title) = &mut self.title {
+            title.leave(&mut io::stderr())?;
+        }
+
+        if let Some(path) = &self.save_path {
+            if self.status() == State::Playing {
+                save::store(path, self)?;
+            } else {
+                save::remove(path)?;
+            }
+        }
+        if let (Some(path), Some(recording)) = (&self.record_path, &mut self.recording) {
+            recording.finish(self.stopwatch.elapsed());
+            replay::store(path, recording)?;
+        }
+        if let Some(hook) = &mut self.hook {
+            for err in hook.wait() {
+                eprintln!("Warning: end-of-game hook failed: {:#}", err);
             }
+        }
+        if finished {
+            self.run_hook();
+            if self.print_share {
+                let card = share::card(self, share::supports_emoji(&self.glyphs));
+                // stdout is for the events with --json
+                if self.json {
+                    eprintln!("{}", card);
+                } else {
+                    println!("{}", card);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hides the board and stops the clock until a key is pressed
+    fn pause(&mut self) {
+        self.paused = true;
+        self.stopwatch.stop();
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        self.stopwatch.start();
+    }
+
+    /// Gives the terminal back to the shell and stops until the shell
+    /// resumes the game, which stays paused meanwhile
+    fn suspend(&mut self, terminal: &mut Terminal<Backend>) -> Result<()> {
+        if !suspend::IS_SUPPORTED {
+            self.notifications
+                .push("Suspending is not supported on this platform");
+            return Ok(());
+        }
+
+        if self.status() == State::Playing {
+            self.pause();
+        }
+        cleanup_terminal(terminal)?;
+        if let Some(title) = &mut self.title {
+            title.leave(&mut io::stderr())?;
+        }
+        suspend::stop();
+        if let Some(title) = &mut self.title {
+            title.enter(&mut io::stderr())?;
+        }
+        restore_terminal(terminal)
+    }
+
+    fn on_signal(&mut self, terminal: &mut Terminal<Backend>, signal: Signal) -> Result<()> {
+        match signal {
+            Signal::Suspend => self.suspend(terminal),
+            // the process may have been stopped without asking, e.g. by
+            // SIGSTOP, which leaves the terminal to whoever took it and
+            // the clock running
+            Signal::Resume => {
+                if !self.paused && self.status() == State::Playing {
+                    self.pause();
+                }
+                restore_terminal(terminal)
+            }
+        }
+    }
+
+    /// Announces the game and starts the clock, if there is anything left
+    /// to play
+    fn begin(&mut self) {
+        self.emit(&GameEvent::NewGame {
+            settings: &self.settings,
+            daily: self.daily,
         });
+        for (guess, hint) in self.guesses.iter().zip(&self.hints) {
+            self.emit(&GameEvent::guess(&self.settings, guess, hint));
+        }
+        self.counter
+            .update(&self.settings, &self.guesses, &self.hints);
 
-        let mut terminal = setup_terminal()?;
+        self.turn_started = self.stopwatch.elapsed();
+        if self.status() == State::Playing {
+            self.stopwatch.start();
+        }
+    }
+
+    /// Why a new game cannot be started in place of this one, if so
+    fn check_new_game(&self) -> Result<(), &'static str> {
+        if self.remote.is_some() {
+            Err("The host chooses the code; you cannot start a new network game")
+        } else if self.daily.is_some() {
+            Err("There is only one daily puzzle a day")
+        } else if self.recording.is_some() {
+            Err("A recording holds a single game; you cannot start a new one")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Starts a new game, asking first if one is under way
+    fn on_new_game(&mut self) {
+        if let Err(message) = self.check_new_game() {
+            self.notifications.push(message);
+        } else if self.guesses.is_empty() {
+            self.restart();
+        } else {
+            self.ask(Confirm::NewGame);
+        }
+    }
+
+    /// Goes on to the next code of a marathon if this one is broken
+    fn next_round(&mut self) -> bool {
+        let streak = match &self.marathon {
+            Some(marathon) if self.status() == State::Won => marathon.streak,
+            _ => return false,
+        };
+        self.notifications
+            .push(format!("Code {} broken; here comes the next", streak));
+        self.restart();
+        true
+    }
+
+    /// Replaces the game with a new one under the same rules
+    fn restart(&mut self) {
+        // a marathon goes on only through broken codes
+        let won = self.status() == State::Won;
+        if let (false, Some(marathon)) = (won, &mut self.marathon) {
+            marathon.reset();
+        }
+
+        self.seed = rand::thread_rng().gen();
+        self.solution = generate_solution(&self.settings, self.seed);
+        self.guesses.clear();
+        self.hints.clear();
+        self.current_guess.0.clear();
+        self.cursor = None;
+        self.show_legend = None;
+        self.stats = None;
+        self.score = None;
+        self.suggestion = None;
+        self.stopwatch = Stopwatch::default();
+        self.counter = CandidateCounter::default();
+        self.reviewer = None;
+        self.show_review = false;
+        self.gave_up = false;
+        self.board_scroll = None;
+        self.animations.clear();
+        if self.assistant.is_some() {
+            self.enable_assist();
+        }
+        self.begin();
+    }
+
+    fn run_hook(&self) {
+        if let Some(hook) = &self.hook {
+            // a broken hook should not make the game itself fail
+            if let Err(err) = hook.run(&Summary::new(self)) {
+                eprintln!("Warning: end-of-game hook failed: {:#}", err);
+            }
+        }
+    }
+
+    /// Runs the hook while the game is on the screen, reporting a failure
+    /// as a notification once it is known
+    fn spawn_hook(&mut self) {
+        if let Some(mut hook) = self.hook.take() {
+            if let Err(err) = hook.spawn(&Summary::new(self)) {
+                self.on_hook_failure(err);
+            }
+            self.hook = Some(hook);
+        }
+    }
+
+    fn on_hook_failure(&mut self, err: anyhow::Error) {
+        self.notifications
+            .warn(format!("End-of-game hook failed: {:#}", err));
+    }
+
+    fn hook_failures(&self) -> Receiver<anyhow::Error> {
+        match &self.hook {
+            Some(hook) => hook.failures().clone(),
+            None => crossbeam_channel::never(),
+        }
+    }
+
+    /// Fires when the next frame of the animations is due
+    fn animation_frame(&self) -> Receiver<Instant> {
+        match self
+            .animations
+            .next_frame(self.settings.holes.get(), Instant::now())
+        {
+            Some(duration) => crossbeam_channel::after(duration),
+            None => crossbeam_channel::never(),
+        }
+    }
+
+    /// Keeps the final screen until the player leaves, so that they can look
+    /// back on the game. Returns whether they asked for a new game.
+    fn linger(
+        &mut self,
+        terminal: &mut Terminal<Backend>,
+        rx: &Receiver<Event>,
+        signals: &Receiver<Signal>,
+    ) -> Result<bool> {
+        self.post_game = true;
+        let new_game = loop {
+            let timeout = match self.notifications.update(Instant::now()) {
+                Some(duration) => crossbeam_channel::after(duration),
+                None => crossbeam_channel::never(),
+            };
+            let frame = self.animation_frame();
 
-        loop {
             terminal.draw(|mut f| {
                 self.draw(&mut f);
             })?;
-
-            if let Event::Key(key) = rx.recv()? {
-                match (key.modifiers, key.code) {
-                    (_, KeyCode::Esc)
-                    | (KeyModifiers::CONTROL, KeyCode::Char('c'))
-                    | (_, KeyCode::Char('q')) => break,
-                    (_, KeyCode::Backspace) | (KeyModifiers::CONTROL, KeyCode::Char('z')) => {
-                        self.on_backspace()
+            self.update_title()?;
+
+            let review_rx = match &self.reviewer {
+                Some(reviewer) => reviewer.receiver().clone(),
+                None => crossbeam_channel::never(),
+            };
+            let hook_rx = self.hook_failures();
+            let event = crossbeam_channel::select! {
+                recv(rx) -> event => Some(event?),
+                recv(hook_rx) -> err => {
+                    if let Ok(err) = err {
+                        self.on_hook_failure(err);
                     }
-                    (_, KeyCode::Enter) | (_, KeyCode::Char(' ')) => self.on_enter(),
-                    (_, KeyCode::Char(c)) => self.on_char(c),
+                    None
+                }
+                recv(review_rx) -> reviews => {
+                    if let (Ok(reviews), Some(reviewer)) = (reviews, &mut self.reviewer) {
+                        reviewer.on_reviews(reviews);
+                    }
+                    None
+                }
+                recv(signals) -> signal => {
+                    self.on_signal(terminal, signal?)?;
+                    None
+                }
+                recv(timeout) -> _ => None,
+                recv(frame) -> _ => None,
+            };
+
+            if let (Some(Event::Key(_)), true) = (event, self.show_help) {
+                self.show_help = false;
+            } else if let Some(Event::Key(key)) = event {
+                match self.keys.action(key, true) {
+                    Some(KeyAction::Quit) => break false,
+                    Some(KeyAction::Help) => self.show_help = true,
+                    Some(KeyAction::Suspend) => self.suspend(terminal)?,
+                    Some(KeyAction::Legend) => self.toggle_legend(),
+                    Some(KeyAction::ScrollUp) => self.scroll_board(terminal.size()?, true),
+                    Some(KeyAction::ScrollDown) => self.scroll_board(terminal.size()?, false),
+                    Some(KeyAction::Analyze) => self.toggle_review(),
+                    Some(KeyAction::Share) => self.share(),
+                    Some(KeyAction::NewGame) => match self.check_new_game() {
+                        Ok(()) => break true,
+                        Err(message) => self.notifications.push(message),
+                    },
                     _ => (),
                 }
             }
+        };
+        self.post_game = false;
 
-            if self.status() != State::Playing {
-                break;
+        Ok(new_game)
+    }
+
+    /// Copies the card of the result to the clipboard, or has it printed on
+    /// leaving when there is no clipboard
+    fn share(&mut self) {
+        let card = share::card(self, true);
+        match share::copy_to_clipboard(&card) {
+            Ok(()) => self
+                .notifications
+                .push("Copied the result to the clipboard"),
+            Err(_) => {
+                self.print_share = true;
+                self.notifications
+                    .push("No clipboard is available; the result is printed when you quit");
             }
         }
+    }
 
+    /// Shows or hides the ratings of the guesses, analyzing them the first
+    /// time
+    fn toggle_review(&mut self) {
+        if self.reviewer.is_none() {
+            self.reviewer = Some(Reviewer::new(&self.settings, &self.guesses, &self.hints));
+        }
+        self.show_review = !self.show_review;
+    }
+
+    /// Draws the final screen and restores the terminal, leaving the screen
+    /// as it is
+    fn close(&mut self, mut terminal: Terminal<Backend>) -> Result<()> {
+        self.notifications.update(Instant::now());
         terminal.draw(|mut f| {
             self.draw(&mut f);
         })?;
 
+        let size = terminal.size()?;
+        let layout = self.board_layout(size.width);
         let height =
             // explanation of bulls & cows
-            2
+            self.header_height()
             // margin
             + 1
             // board
-            + self.opt.guesses.get() as u16 + 1
+            + layout.board_height(self.visible_rows(&layout, size.height))
             // margin
             + 1
             // messages
-            + 2
+            + self.messages_height()
             // margin
             + 1;
-        let mut stdout = std::io::stdout();
-        crossterm::queue!(stdout, crossterm::cursor::MoveTo(0, height))?;
-        stdout.flush()?;
+        // the TUI is drawn on stderr so that stdout is left for --json
+        let mut stderr = std::io::stderr();
+        crossterm::queue!(stderr, crossterm::cursor::MoveTo(0, height))?;
+        stderr.flush()?;
+
+        cleanup_terminal(&mut terminal)
+    }
+
+    fn status(&self) -> State {
+        let holes = self.settings.holes.get();
+        if !self.hints_withheld() && self.hints.iter().any(|hint| hint.bulls == holes) {
+            return State::Won;
+        }
+
+        if self.guesses.len() >= self.settings.guesses.get() || self.is_time_up() || self.gave_up {
+            State::Lost
+        } else {
+            State::Playing
+        }
+    }
+
+    /// Whether the hints are kept from the player under --static, which
+    /// reveals them for the last guess
+    fn hints_withheld(&self) -> bool {
+        self.settings.static_hints && self.guesses.len() + 1 < self.settings.guesses.get()
+    }
+
+    /// Whether the hints are hidden on the board
+    fn are_hints_hidden(&self) -> bool {
+        self.hints_withheld() && self.status() == State::Playing
+    }
+
+    /// Whether the number of codes left tells more than the hints shown
+    fn can_count_codes(&self) -> bool {
+        !self.settings.blind && !self.are_hints_hidden()
+    }
+
+    fn time_left(&self) -> Option<Duration> {
+        let limit = Duration::from_secs(self.settings.time_limit?.get());
+        Some(limit.saturating_sub(self.stopwatch.elapsed()))
+    }
+
+    fn is_time_up(&self) -> bool {
+        self.time_left() == Some(Duration::from_secs(0))
+    }
+
+    /// Time left to make the current guess under --turn-time
+    fn turn_time_left(&self) -> Option<Duration> {
+        let limit = Duration::from_secs(self.settings.turn_time?.get());
+        let turn = self.stopwatch.elapsed().saturating_sub(self.turn_started);
+        Some(limit.saturating_sub(turn))
+    }
+
+    /// Fills and submits the current row, or forfeits the turn, once the
+    /// turn time runs out
+    fn check_turn_time(&mut self) {
+        if self.status() != State::Playing
+            || self.pending_guess.is_some()
+            || self.turn_time_left() != Some(Duration::from_secs(0))
+        {
+            return;
+        }
+
+        let guesses = self.guesses.len();
+        // the host knows nothing of lost turns, so a network game fills the row
+        if self.settings.forfeit_turn && self.remote.is_none() {
+            self.forfeit_turn();
+        } else {
+            self.complete_turn();
+        }
+        if self.guesses.len() == guesses && self.pending_guess.is_none() {
+            // the row was rejected, so the player gets another turn to fix it
+            self.turn_started = self.stopwatch.elapsed();
+        }
+    }
+
+    /// Spends the current turn on an empty row without a hint
+    fn forfeit_turn(&mut self) {
+        self.record(Action::Forfeit);
+        self.notifications.push(format!(
+            "Time is up; guess {} is lost",
+            self.guesses.len() + 1
+        ));
+        self.current_guess.0.clear();
+        self.add_guess(Guess::default(), Hint::default());
+    }
+
+    /// Fills the rest of the current row at random and makes the guess
+    fn complete_turn(&mut self) {
+        let mut rng = rand::thread_rng();
+        let mut completion = self.random_completion(&mut rng);
+        if self.settings.hard {
+            // look for a row which is not rejected
+            for _ in 1..COMPLETION_ATTEMPTS {
+                let mut guess = self.current_guess.clone();
+                guess.0.extend(&completion);
+                let explanation = explain::explain(
+                    &guess,
+                    &self.guesses,
+                    &self.hints,
+                    self.settings.code_colors(),
+                );
+                if explanation.is_none() {
+                    break;
+                }
+                completion = self.random_completion(&mut rng);
+            }
+        }
+
+        self.notifications
+            .push("Time is up; the rest of the row is filled at random");
+        // go through the editing actions so that recordings reproduce the row
+        while self.cursor() < self.current_guess.0.len() {
+            self.move_cursor(false);
+        }
+        for color in completion {
+            self.place(color);
+        }
+        self.on_enter();
+    }
+
+    /// Colors to fill the empty holes of the current row with
+    fn random_completion(&self, rng: &mut impl Rng) -> Vec<usize> {
+        let colors = self.settings.code_colors();
+        let mut row = self.current_guess.0.clone();
+        while row.len() < self.settings.holes.get() {
+            let color = if self.settings.no_duplicate {
+                match (0..colors).filter(|color| !row.contains(color)).choose(rng) {
+                    Some(color) => color,
+                    None => break,
+                }
+            } else {
+                rng.gen_range(0, colors)
+            };
+            row.push(color);
+        }
+        row.split_off(self.current_guess.0.len())
+    }
+
+    fn on_game_over(&mut self) {
+        self.stopwatch.stop();
+        self.animations
+            .on_game_over(self.status() == State::Won, Instant::now());
+
+        self.record_daily();
+
+        let guesses = if self.status() == State::Won {
+            Some(self.guesses.len())
+        } else {
+            None
+        };
+
+        if let (Some(marathon), Some(_)) = (&mut self.marathon, guesses) {
+            marathon.on_win(self.stopwatch.elapsed());
+            if let Err(err) = stats::update_streak(&self.settings, marathon.streak) {
+                self.notifications
+                    .push(format!("Failed to record the streak: {}", err));
+            }
+        }
+
+        match stats::update(&self.settings, guesses) {
+            Ok(record) => {
+                if self.suggest_difficulty {
+                    self.suggestion = record.suggestion(&self.settings);
+                }
+                self.stats = Some(record);
+            }
+            Err(err) => self
+                .notifications
+                .push(format!("Failed to update statistics: {}", err)),
+        }
+
+        if let Some(guesses) = guesses {
+            let entry = Entry::new(
+                &self.player,
+                &self.settings,
+                guesses,
+                self.stopwatch.elapsed(),
+            );
+            let score = entry.score;
+            match leaderboard::update(entry) {
+                Ok(rank) => self.score = Some((score, rank)),
+                Err(err) => self
+                    .notifications
+                    .push(format!("Failed to update the leaderboard: {}", err)),
+            }
+        }
+    }
+
+    fn messages_height(&self) -> u16 {
+        // result, and notifications or margin
+        let height = 1 + self.notifications.len().max(1) as u16;
+        match &self.stats {
+            // summary, suggestion, and histogram
+            Some(_) => height + 1 + self.suggestion.is_some() as u16 + self.histogram_rows() as u16,
+            None => height,
+        }
+    }
+
+    fn histogram_rows(&self) -> usize {
+        self.settings.guesses.get()
+    }
+
+    /// Whether the explanation of bulls & cows is shown in full or
+    /// collapsed into a single line
+    fn is_legend_expanded(&self) -> bool {
+        !self.settings.blind
+            && self
+                .show_legend
+                .unwrap_or(self.guesses.len() < LEGEND_AUTO_HIDE_GUESSES)
+    }
+
+    fn header_height(&self) -> u16 {
+        if self.is_legend_expanded() {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn toggle_legend(&mut self) {
+        self.show_legend = Some(!self.is_legend_expanded());
+    }
+
+    fn record(&mut self, action: Action) {
+        if let Some(recording) = &mut self.recording {
+            recording.push(self.stopwatch.elapsed(), action);
+        }
+    }
+
+    /// Lets colors be typed as in the mode along with the number keys.
+    /// Digits are typed only as themselves.
+    fn set_input_mode(&mut self, mode: InputMode) {
+        let mode = if self.settings.digits {
+            InputMode::Numbers
+        } else {
+            mode
+        };
+        let names: Vec<_> = (0..self.settings.code_colors())
+            .map(|color| self.color_name(color))
+            .collect();
+        // letters bound to actions, macros or colors keep working
+        let keys = &self.keys;
+        let macros = &self.macros;
+        self.input = ColorInput::new(mode, &names, |c| {
+            keys.check_macro_key(c).is_err() || macros.get(c).is_some()
+        });
+    }
+
+    /// Keys selecting the colors, e.g. "1-6 or brgymw"
+    fn color_keys(&self) -> String {
+        let keys = self.keys.color_keys(&self.settings);
+        match self.input.mode() {
+            InputMode::Numbers => keys,
+            InputMode::Initials => {
+                let initials: String = (0..self.settings.code_colors())
+                    .filter_map(|color| self.input.abbreviation(color))
+                    .collect();
+                format!("{} or {}", keys, initials)
+            }
+            InputMode::Names => format!("{} or color names", keys),
+        }
+    }
+
+    /// e.g. "Typing bl: blue or black; press tab to complete"
+    fn typing_message(&self) -> String {
+        let completions = self
+            .input
+            .completions()
+            .into_iter()
+            .map(|color| self.color_name(color))
+            .collect::<Vec<_>>();
+        let completions = match completions.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => String::new(),
+        };
+        format!(
+            "Typing {}: {}; press tab to complete",
+            self.input.typed(),
+            completions
+        )
+    }
+
+    fn on_char(&mut self, c: char) {
+        let keys = self.keys.color_keys(&self.settings);
+        match self.keys.color(c, &self.settings) {
+            Some(Some(color)) => self.place(color),
+            Some(None) => self
+                .notifications
+                .push(format!("There is no color {}; choose from {}", c, keys)),
+            None => self.notifications.push(format!(
+                "Unknown key '{}'; press {} to select colors, {} for help",
+                c,
+                keys,
+                self.keys.key(KeyAction::Help)
+            )),
+        }
+    }
+
+    fn ask(&mut self, confirm: Confirm) {
+        if confirm == Confirm::GiveUp && self.remote.is_some() {
+            self.notifications
+                .push("The host has the code; you cannot give up in a network game");
+            return;
+        }
+        self.confirm = Some(confirm);
+    }
+
+    fn on_confirm(&mut self, confirm: Confirm) {
+        match confirm {
+            Confirm::GiveUp => self.give_up(),
+            Confirm::NewGame => self.restart(),
+        }
+    }
+
+    /// Loses the game, which reveals the solution
+    fn give_up(&mut self) {
+        self.record(Action::GiveUp);
+        self.gave_up = true;
+    }
+
+    /// Replaces the current row with the guess the macro expands to
+    fn run_macro(&mut self, key: char) {
+        let colors = match self
+            .macros
+            .get(key)
+            .map(|pattern| pattern.expand(&self.settings))
+        {
+            Some(Ok(colors)) => colors,
+            Some(Err(message)) => {
+                self.notifications.push(message);
+                return;
+            }
+            None => return,
+        };
+
+        // go through the editing actions so that recordings reproduce the row
+        while self.cursor() < self.current_guess.0.len() {
+            self.move_cursor(false);
+        }
+        for _ in 0..self.current_guess.0.len() {
+            self.on_backspace();
+        }
+        for color in colors {
+            self.place(color);
+        }
+    }
+
+    /// Puts the color at the cursor, replacing the peg there unless in
+    /// insert mode
+    fn place(&mut self, number: usize) {
+        self.record(Action::Place(number));
+
+        let cursor = self.cursor();
+        let replaced =
+            Some(cursor).filter(|&i| i < self.current_guess.0.len() && !self.insert_mode);
+        if replaced.is_none() && self.current_guess.0.len() >= self.settings.holes.get() {
+            self.notifications.push(format!(
+                "The row is full; press {} to make a guess",
+                self.keys.key(KeyAction::Submit)
+            ));
+            return;
+        }
+
+        let duplicate = self
+            .current_guess
+            .0
+            .iter()
+            .enumerate()
+            .any(|(i, &color)| color == number && Some(i) != replaced);
+        if self.settings.no_duplicate && duplicate {
+            self.notifications.push(format!(
+                "Color {} is already in the row; colors cannot duplicate",
+                self.settings.color_number(number)
+            ));
+            return;
+        }
+
+        match replaced {
+            Some(i) => self.current_guess.0[i] = number,
+            None => self.current_guess.0.insert(cursor, number),
+        }
+        self.set_cursor(cursor + 1);
+    }
+
+    /// Position of the cursor in the current row, which is past the last
+    /// peg unless it has been moved
+    fn cursor(&self) -> usize {
+        let len = self.current_guess.0.len();
+        self.cursor.map_or(len, |cursor| cursor.min(len))
+    }
+
+    fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = Some(cursor).filter(|&cursor| cursor < self.current_guess.0.len());
+    }
+
+    fn move_cursor(&mut self, left: bool) {
+        self.record(if left {
+            Action::CursorLeft
+        } else {
+            Action::CursorRight
+        });
+        let cursor = self.cursor();
+        if left {
+            self.set_cursor(cursor.saturating_sub(1));
+        } else {
+            self.set_cursor(cursor + 1);
+        }
+    }
+
+    fn toggle_insert(&mut self) {
+        self.record(Action::ToggleInsert);
+        self.insert_mode = !self.insert_mode;
+    }
+
+    fn save(&mut self) {
+        let message = match &self.save_path {
+            Some(path) => match save::store(path, self) {
+                Ok(()) => format!("Saved to {}", path.display()),
+                Err(err) => format!("Failed to save: {}", err),
+            },
+            None => "Specify --save <path> to save the game".to_string(),
+        };
+        self.notifications.push(message);
+    }
+
+    /// Removes the peg before the cursor
+    fn on_backspace(&mut self) {
+        self.record(Action::Undo);
+        let cursor = self.cursor();
+        if cursor == 0 {
+            self.notifications.push("Nothing to undo");
+            return;
+        }
+        self.current_guess.0.remove(cursor - 1);
+        self.set_cursor(cursor - 1);
+    }
+
+    /// Removes the peg at the cursor
+    fn on_delete(&mut self) {
+        self.record(Action::Delete);
+        let cursor = self.cursor();
+        if cursor >= self.current_guess.0.len() {
+            self.notifications.push("There is no peg at the cursor");
+            return;
+        }
+        self.current_guess.0.remove(cursor);
+        self.set_cursor(cursor);
+    }
+
+    fn on_enter(&mut self) {
+        self.record(Action::Submit);
+        if self.remote.is_some() {
+            network::send_guess(self);
+        } else if let Err(message) = self.submit() {
+            self.notifications.push(message);
+        }
+    }
+
+    /// e.g. "codebreaker — guess 5/8"
+    fn update_title(&mut self) -> Result<()> {
+        let max_guesses = self.settings.guesses.get();
+        let (text, progress) = match self.status() {
+            State::Playing => (
+                format!("guess {}/{}", self.guesses.len() + 1, max_guesses),
+                Some((self.guesses.len() * 100 / max_guesses) as u8),
+            ),
+            State::Won => (format!("won in {}", self.guesses.len()), None),
+            State::Lost => ("lost".to_owned(), None),
+        };
+
+        if let Some(title) = &mut self.title {
+            let text = format!("{} \u{2014} {}", env!("CARGO_PKG_NAME"), text);
+            title.update(&mut io::stderr(), &text, progress)?;
+        }
+        Ok(())
+    }
+
+    fn emit(&self, event: &GameEvent) {
+        if self.json {
+            // a closed stdout should not interrupt the game
+            let _ = events::write(io::stdout(), event);
+        }
+    }
 
-        cleanup_terminal(&mut terminal)?;
+    /// Makes a guess with the current row, or explains why it cannot be made
+    fn submit(&mut self) -> Result<Hint, String> {
+        self.check_guess()?;
 
-        Ok(())
+        let hint = calc_hint(
+            &self.current_guess,
+            &self.solution,
+            self.settings.code_colors(),
+        );
+        let guess = std::mem::take(&mut self.current_guess);
+        self.add_guess(guess, hint.clone());
+
+        Ok(hint)
     }
 
-    fn status(&self) -> State {
-        if let Some(hint) = self.hints.last() {
-            if hint.bulls == self.opt.holes.get() {
-                return State::Won;
-            }
+    /// Checks whether the current row can be made a guess
+    fn check_guess(&self) -> Result<(), String> {
+        let holes = self.settings.holes.get();
+        if self.current_guess.0.len() != holes {
+            return Err(format!("Fill all {} holes before making a guess", holes));
         }
 
-        if self.guesses.len() >= self.opt.guesses.get() {
-            State::Lost
-        } else {
-            State::Playing
+        if self.settings.hard {
+            let explanation = explain::explain(
+                &self.current_guess,
+                &self.guesses,
+                &self.hints,
+                self.settings.code_colors(),
+            );
+            if let Some(explanation) = explanation {
+                return Err(explanation.describe(|c| self.color_name(c)));
+            }
         }
+
+        Ok(())
     }
 
-    fn on_char(&mut self, c: char) {
-        if self.current_guess.0.len() >= self.opt.holes.get() {
-            return;
+    fn add_guess(&mut self, guess: Guess, hint: Hint) {
+        self.emit(&GameEvent::guess(&self.settings, &guess, &hint));
+        // forfeited turns are all empty rows
+        let repeated = self
+            .guesses
+            .iter()
+            .position(|g| *g == guess)
+            .filter(|_| !guess.0.is_empty());
+        if let Some(i) = repeated {
+            self.notifications.warn(format!(
+                "Guess {} is the same as guess {} and tells nothing new",
+                self.guesses.len() + 1,
+                i + 1
+            ));
+        }
+        self.guesses.push(guess);
+        self.hints.push(hint);
+        self.turn_started = self.stopwatch.elapsed();
+        self.animations
+            .on_guess(self.guesses.len() - 1, Instant::now());
+        self.counter
+            .update(&self.settings, &self.guesses, &self.hints);
+        // follow the latest guess
+        self.board_scroll = None;
+        self.cursor = None;
+
+        if let Some(assistant) = &mut self.assistant {
+            assistant.refresh(&self.settings, &self.guesses, &self.hints);
         }
 
-        if let Some(number) = parse_color_number(c) {
-            if self.opt.no_duplicate && self.current_guess.0.iter().any(|x| *x == number) {
-                return;
-            }
+        // quitting halfway must not give another try at the daily puzzle
+        if self.guesses.len() == 1 {
+            self.record_daily();
+        }
+    }
 
-            if number < self.opt.colors.get() {
-                self.current_guess.0.push(number);
+    fn record_daily(&mut self) {
+        if let Some(day) = self.daily {
+            if let Err(err) = daily::record(day, self) {
+                self.notifications
+                    .push(format!("Failed to record the daily puzzle: {}", err));
             }
         }
     }
 
-    fn on_backspace(&mut self) {
-        self.current_guess.0.pop();
+    fn board_layout(&self, width: u16) -> BoardLayout {
+        BoardLayout::new(self.settings.holes.get(), width)
     }
 
-    fn on_enter(&mut self) {
-        if self.current_guess.0.len() != self.opt.holes.get() {
+    /// Smallest terminal the game can be played in, as (width, height)
+    fn min_size(&self, layout: &BoardLayout) -> (u16, u16) {
+        let width = layout.indent() + layout.board_width();
+        let rows = MIN_VISIBLE_ROWS.min(self.settings.guesses.get());
+        let height = self.header_height() + 1 + layout.board_height(rows) + 1 + FOOTER_HEIGHT;
+        (width, height)
+    }
+
+    /// Number of rows of guesses which fit in a terminal `height` lines tall
+    /// along with the rest of the screen
+    fn visible_rows(&self, layout: &BoardLayout, height: u16) -> usize {
+        let board_height = height.saturating_sub(self.header_height() + 1 + 1 + FOOTER_HEIGHT);
+        layout.visible_rows(self.settings.guesses.get(), board_height)
+    }
+
+    /// Index of the lowest row shown, counted from the first guess. Unless
+    /// scrolled, the row being filled in is kept at the top.
+    fn first_visible_row(&self, visible_rows: usize) -> usize {
+        let last = self.settings.guesses.get().saturating_sub(visible_rows);
+        let current = self.guesses.len() + self.pending_guess.is_some() as usize;
+        let follow = (current + 1).saturating_sub(visible_rows);
+        self.board_scroll.unwrap_or(follow).min(last)
+    }
+
+    /// Scrolls the board a page up, toward the later rows, or down
+    fn scroll_board(&mut self, size: Rect, up: bool) {
+        let layout = self.board_layout(size.width);
+        let visible_rows = self.visible_rows(&layout, size.height);
+        if visible_rows == self.settings.guesses.get() {
             return;
         }
 
-        let hint = calc_hint(&self.current_guess, &self.solution, self.opt.colors.get());
-        self.guesses.push(std::mem::take(&mut self.current_guess));
-        self.hints.push(hint);
+        // keep a row of the previous page in sight
+        let page = visible_rows.saturating_sub(1).max(1);
+        let first = self.first_visible_row(visible_rows);
+        self.board_scroll = Some(if up {
+            first + page
+        } else {
+            first.saturating_sub(page)
+        });
     }
 
     fn draw(&self, f: &mut Frame<Backend>) {
-        let board_height = self.opt.guesses.get()
-            // solution row
-            + 1
+        let size = f.size();
+        let layout = self.board_layout(size.width);
+        let (min_width, min_height) = self.min_size(&layout);
+        if size.width < min_width || size.height < min_height {
+            let text = [Text::raw(format!(
+                "The terminal is too small: {}x{} is needed, but it is {}x{}",
+                min_width, min_height, size.width, size.height
+            ))];
+            f.render_widget(self.paragraph(text.iter()).wrap(true), size);
+            return;
+        }
+
+        if self.paused {
+            self.draw_paused(f, size);
+            return;
+        }
+
+        let visible_rows = self.visible_rows(&layout, size.height);
+        let first_row = self.first_visible_row(visible_rows);
+        let board_height = layout.board_height(visible_rows)
             // between board and message
             + 1;
 
         let chunks = Layout::default()
             .constraints([
+                Constraint::Length(self.header_height()),
                 Constraint::Length(1),
-                Constraint::Length(2),
-                Constraint::Length(board_height as u16),
+                Constraint::Length(board_height),
                 Constraint::Length(1),
                 Constraint::Min(1),
             ])
             .split(f.size());
 
-        let text = vec![
-            Text::styled(CIRCLE, Style::default().fg(BULL_COLOR)),
-            Text::raw(" Correct color, correct position"),
-        ];
-        f.render_widget(Paragraph::new(text.iter()), chunks[0]);
-
-        let text = vec![
-            Text::styled(CIRCLE, Style::default().fg(COW_COLOR)),
-            Text::raw(" Correct color, wrong position"),
-        ];
-        f.render_widget(Paragraph::new(text.iter()), chunks[1]);
-
-        self.draw_board(f, chunks[2]);
+        self.draw_header(f, chunks[0]);
+        self.draw_board(f, &layout, first_row, visible_rows, chunks[2]);
+
+        let guesses = self.settings.guesses.get();
+        if visible_rows < guesses {
+            let text = [Text::raw(format!(
+                "Rows {}-{} of {}; press {}/{} to scroll",
+                first_row + 1,
+                first_row + visible_rows,
+                guesses,
+                self.keys.key(KeyAction::ScrollUp),
+                self.keys.key(KeyAction::ScrollDown)
+            ))];
+            let margin = Rect::new(chunks[2].x, chunks[2].bottom() - 1, chunks[2].width, 1);
+            f.render_widget(self.paragraph(text.iter()), margin);
+        }
 
         match self.status() {
             State::Playing => {
-                let text = vec![if self.current_guess.0.len() < self.opt.holes.get() {
-                    Text::raw("Press number keys to select colors")
+                let text = [if let Some(caption) = &self.caption {
+                    Text::raw(caption)
+                } else if let Some(confirm) = self.confirm {
+                    Text::raw(confirm.prompt())
+                } else if !self.input.typed().is_empty() {
+                    Text::raw(self.typing_message())
+                } else if self.current_guess.0.len() < self.settings.holes.get() {
+                    Text::raw(format!(
+                        "Press {} to {}, {} for help",
+                        self.color_keys(),
+                        if self.settings.digits {
+                            "enter digits"
+                        } else {
+                            "select colors"
+                        },
+                        self.keys.key(KeyAction::Help)
+                    ))
                 } else {
-                    Text::raw("Press enter to make a guess")
+                    Text::raw(format!(
+                        "Press {} to make a guess",
+                        self.keys.key(KeyAction::Submit)
+                    ))
                 }];
-                f.render_widget(Paragraph::new(text.iter()), chunks[3]);
-
-                if !self.current_guess.0.is_empty() {
-                    let text = vec![Text::raw("Press backspace to undo")];
-                    f.render_widget(Paragraph::new(text.iter()), chunks[4]);
+                f.render_widget(self.paragraph(text.iter()), chunks[3]);
+
+                let chunks = Layout::default()
+                    .constraints([
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                        Constraint::Min(1),
+                    ])
+                    .split(chunks[4]);
+
+                // flashes while a warning is shown
+                let style = if self.notifications.has_visible_warning() {
+                    self.theme.text.modifier(Modifier::REVERSED)
+                } else {
+                    self.theme.text
+                };
+                let text = [Text::styled(
+                    format!(
+                        "{}  Elapsed {}",
+                        self.peg_count_message(),
+                        format_duration(self.stopwatch.elapsed())
+                    ),
+                    style,
+                )];
+                f.render_widget(self.paragraph(text.iter()), chunks[0]);
+
+                if !self.current_guess.0.is_empty() && self.caption.is_none() {
+                    let text = [Text::raw(format!(
+                        "Press {} to undo",
+                        self.keys.key(KeyAction::Undo)
+                    ))];
+                    f.render_widget(self.paragraph(text.iter()), chunks[1]);
                 }
+
+                self.draw_notifications(f, chunks[3]);
             }
-            State::Won => {
-                let text = vec![Text::raw("You won!")];
-                f.render_widget(Paragraph::new(text.iter()), chunks[3]);
+            State::Won | State::Lost => {
+                let message = match &self.caption {
+                    Some(caption) => caption.clone(),
+                    None => self.result_message(),
+                };
+                let style = if self.animations.is_flashing(Instant::now()) {
+                    Style::default()
+                        .fg(self.theme.bull)
+                        .modifier(Modifier::REVERSED)
+                } else {
+                    self.theme.text
+                };
+                let text = [Text::styled(message, style)];
+                f.render_widget(self.paragraph(text.iter()), chunks[3]);
+                self.draw_stats(f, chunks[4]);
             }
-            State::Lost => {
-                let text = vec![Text::raw("You lost")];
-                f.render_widget(Paragraph::new(text.iter()), chunks[3]);
+        }
+
+        if self.show_help {
+            help::draw(self, f);
+        }
+    }
+
+    /// Draws only a note in the middle of the screen, so that the board
+    /// cannot be studied while the clock is stopped
+    fn draw_paused(&self, f: &mut Frame<Backend>, area: Rect) {
+        let text = [
+            Text::raw("Paused\n\n"),
+            Text::raw(format!(
+                "Press any key to resume, {} to quit",
+                self.keys.key(KeyAction::Quit)
+            )),
+        ];
+        let height = 3.min(area.height);
+        let area = Rect::new(
+            area.x,
+            area.y + (area.height - height) / 2,
+            area.width,
+            height,
+        );
+        f.render_widget(
+            self.paragraph(text.iter()).alignment(Alignment::Center),
+            area,
+        );
+    }
+
+    /// e.g. "You won! (1:23)  Score 1234"
+    fn result_message(&self) -> String {
+        if self.status() != State::Won {
+            return if self.gave_up {
+                "You gave up".to_owned()
+            } else if self.is_time_up() {
+                "Time is up. You lost".to_owned()
+            } else {
+                "You lost".to_owned()
+            };
+        }
+
+        let mut message = format!("You won! ({})", format_duration(self.stopwatch.elapsed()));
+        if let Some((score, rank)) = self.score {
+            message += &format!("  Score {}", score);
+            if let Some(rank) = rank {
+                message += &format!(" (#{} on the leaderboard)", rank + 1);
             }
         }
+        message
     }
 
-    fn draw_board(&self, f: &mut Frame<Backend>, area: Rect) {
-        let board_width = self.opt.holes.get() *
-        (
-            // codes
-            2
-            // keys
-            + 1
-        )
-            // between codes and keys
-            + 1
-            // between keys and legend
-            + 2;
+    fn draw_stats(&self, f: &mut Frame<Backend>, area: Rect) {
+        let chunks = Layout::default()
+            .constraints([
+                Constraint::Length(self.notifications.len().max(1) as u16),
+                Constraint::Length(1),
+                Constraint::Length(self.suggestion.is_some() as u16),
+                Constraint::Min(1),
+            ])
+            .split(area);
+
+        if self.post_game && self.notifications.len() == 0 {
+            let mut message = String::from("Press ");
+            if self.check_new_game().is_ok() {
+                message += &format!("{} for a new game, ", self.keys.key(KeyAction::NewGame));
+            }
+            message += &format!(
+                "{} to {}, {} to share, {} to quit",
+                self.keys.key(KeyAction::Analyze),
+                if self.show_review {
+                    "hide the analysis"
+                } else {
+                    "analyze your guesses"
+                },
+                self.keys.key(KeyAction::Share),
+                self.keys.key(KeyAction::Quit)
+            );
+            let text = [Text::raw(message)];
+            f.render_widget(self.paragraph(text.iter()), chunks[0]);
+        } else {
+            self.draw_notifications(f, chunks[0]);
+        }
+
+        let stats = match &self.stats {
+            Some(stats) => stats,
+            None => return,
+        };
+
+        let text = [Text::raw(stats.summary())];
+        f.render_widget(self.paragraph(text.iter()), chunks[1]);
+
+        if let Some(suggestion) = &self.suggestion {
+            let text = [Text::raw(suggestion)];
+            f.render_widget(self.paragraph(text.iter()), chunks[2]);
+        }
+
+        let text: Vec<_> = stats
+            .histogram(self.histogram_rows(), self.glyphs.bar)
+            .into_iter()
+            .map(|line| Text::raw(line + "\n"))
+            .collect();
+        f.render_widget(self.paragraph(text.iter()), chunks[3]);
+    }
+
+    fn draw_notifications(&self, f: &mut Frame<Backend>, area: Rect) {
+        let text: Vec<_> = self
+            .notifications
+            .visible()
+            .map(|message| Text::raw(format!("{}\n", message)))
+            .collect();
+        f.render_widget(self.paragraph(text.iter()), area);
+    }
+
+    /// Hole the cursor is on while the game is played, if the row is not
+    /// full
+    fn cursor_hole(&self) -> Option<usize> {
+        Some(self.cursor())
+            .filter(|&cursor| cursor < self.settings.holes.get())
+            .filter(|_| self.status() == State::Playing)
+    }
+
+    /// e.g. "2/4 pegs placed (holes 3, 4 empty)"
+    fn peg_count_message(&self) -> String {
+        let holes = self.settings.holes.get();
+        let placed = self.current_guess.0.len();
+        let empty_holes: Vec<_> = (placed..holes).map(|i| (i + 1).to_string()).collect();
+
+        let mut message = format!("{}/{} pegs placed", placed, holes);
+        match empty_holes.len() {
+            0 => (),
+            1 => message += &format!(" (hole {} empty)", empty_holes[0]),
+            _ => message += &format!(" (holes {} empty)", empty_holes.join(", ")),
+        }
+        let cursor = self.cursor();
+        if cursor < placed {
+            message += &format!(
+                ", {} hole {}",
+                if self.insert_mode {
+                    "inserting at"
+                } else {
+                    "replacing"
+                },
+                cursor + 1
+            );
+        }
+        message
+    }
+
+    fn draw_header(&self, f: &mut Frame<Backend>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(LEGEND_WIDTH), Constraint::Min(1)])
+            .split(area);
+        let area = chunks[0];
+
+        let time_left = self.time_left().map(|time_left| {
+            // round up so that the countdown reaches zero just when the time is up
+            let time_left = time_left + Duration::from_secs(1) - Duration::from_nanos(1);
+            format!("Time left {}", format_duration(time_left))
+        });
+        let marathon = self
+            .marathon
+            .as_ref()
+            .map(|marathon| marathon.status(self.stopwatch.elapsed()));
+        let status = time_left.into_iter().chain(marathon).join("  ");
+        if !status.is_empty() {
+            let text = [Text::raw(status)];
+            f.render_widget(self.paragraph(text.iter()), chunks[1]);
+        }
+
+        if self.settings.blind {
+            // a single kind of key peg needs no more explanation
+            let text = [
+                Text::styled(self.glyphs.cow, Style::default().fg(self.theme.cow)),
+                Text::raw(format!(" Correct {}, any position", self.peg_noun())),
+            ];
+            f.render_widget(self.paragraph(text.iter()), area);
+            return;
+        }
+
+        if !self.is_legend_expanded() {
+            let text = [
+                Text::styled(self.glyphs.bull, Style::default().fg(self.theme.bull)),
+                Text::raw(" Correct position  "),
+                Text::styled(self.glyphs.cow, Style::default().fg(self.theme.cow)),
+                Text::raw(" Wrong position"),
+            ];
+            f.render_widget(self.paragraph(text.iter()), area);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .constraints([Constraint::Length(1), Constraint::Length(1)])
+            .split(area);
+
+        let text = [
+            Text::styled(self.glyphs.bull, Style::default().fg(self.theme.bull)),
+            Text::raw(format!(" Correct {}, correct position", self.peg_noun())),
+        ];
+        f.render_widget(self.paragraph(text.iter()), chunks[0]);
+
+        let text = [
+            Text::styled(self.glyphs.cow, Style::default().fg(self.theme.cow)),
+            Text::raw(format!(" Correct {}, wrong position", self.peg_noun())),
+        ];
+        f.render_widget(self.paragraph(text.iter()), chunks[1]);
+    }
+
+    fn paragraph<'t, T>(&self, text: T) -> Paragraph<'_, 't, T>
+    where
+        T: Iterator<Item = &'t Text<'t>>,
+    {
+        Paragraph::new(text).style(self.theme.text)
+    }
 
+    /// Draws `visible_rows` rows of guesses from `first_row` up, under the
+    /// solution row
+    fn draw_board(
+        &self,
+        f: &mut Frame<Backend>,
+        layout: &BoardLayout,
+        first_row: usize,
+        visible_rows: usize,
+        area: Rect,
+    ) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
-                Constraint::Length(2),
-                Constraint::Length(board_width as u16),
+                Constraint::Length(layout.indent()),
+                Constraint::Length(layout.board_width()),
                 Constraint::Min(1),
             ])
             .split(area);
@@ -314,90 +2427,468 @@ impl<'a> Game<'a> {
         let guesses = self
             .guesses
             .iter()
+            .chain(self.pending_guess.iter())
             .chain(iter::once(&self.current_guess))
             .chain(iter::repeat(&empty_guess))
-            .take(self.opt.guesses.get());
+            .skip(first_row)
+            .take(visible_rows);
 
-        let empty_hint = Default::default();
+        // hints withheld under --static are left out
+        let now = Instant::now();
+        let hidden = self.are_hints_hidden();
         let hints = self
             .hints
             .iter()
-            .chain(iter::repeat(&empty_hint))
-            .take(self.opt.guesses.get());
-
-        let constraints = vec![Constraint::Length(1); self.opt.guesses.get() + 1]; // +1 for solution
+            .enumerate()
+            .map(|(i, hint)| Some(self.animations.hint(i, hint, now)).filter(|_| !hidden))
+            .chain(iter::repeat(Some(Hint::default())))
+            .skip(first_row)
+            .take(visible_rows);
+
+        let constraints: Vec<_> = iter::once(Constraint::Length(1)) // solution
+            .chain(iter::repeat_n(
+                Constraint::Length(layout.row_height()),
+                visible_rows,
+            ))
+            .collect();
         let rows = Layout::default().constraints(constraints).split(chunks[1]);
 
         let solution_row = rows[0];
-        let solution = if self.status() == State::Playing {
-            &empty_guess
+        if self.status() != State::Playing || self.show_solution {
+            let shown = self
+                .animations
+                .solution_pegs(self.settings.holes.get(), now);
+            let solution = Guess(self.solution.0[..shown].to_vec());
+            self.draw_row(f, layout, &solution, None, None, solution_row);
+        } else if let Some(likely_colors) = self.overlay() {
+            self.draw_overlay(f, layout, likely_colors, solution_row);
+        } else {
+            self.draw_row(f, layout, &empty_guess, None, None, solution_row);
+        }
+
+        // the row being edited, unless it waits for the host
+        let current_row = self.guesses.len() + self.pending_guess.iter().count();
+        let cursor = self.cursor_hole().filter(|_| self.pending_guess.is_none());
+        let mut current_rect = None;
+        let rows_shown = izip!(guesses, hints, rows.iter().skip(1).rev()).enumerate();
+        for (i, (guess, hint, row)) in rows_shown {
+            let is_current = first_row + i == current_row;
+            if is_current {
+                current_rect = Some(*row);
+            }
+            // a forfeited turn has no hint to show
+            let forfeited = first_row + i < self.guesses.len() && guess.0.is_empty();
+            let hint = hint.filter(|_| !forfeited);
+            self.draw_row(
+                f,
+                layout,
+                guess,
+                hint.as_ref(),
+                cursor.filter(|_| is_current),
+                *row,
+            );
+            if hint.is_none() {
+                let marker = if forfeited { "-" } else { "?" };
+                let text = [Text::styled(marker, self.theme.empty)];
+                let hint_area = Rect::new(row.x, row.y, layout.hint_width(), 1);
+                f.render_widget(self.paragraph(text.iter()), hint_area);
+            }
+        }
+
+        if let (true, Some(reviewer)) = (self.show_review, &self.reviewer) {
+            self.draw_review(f, reviewer, first_row, &rows, chunks[2]);
+            return;
+        }
+
+        match &self.assistant {
+            Some(assistant) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([
+                        Constraint::Length(self.legend_width() + 3),
+                        Constraint::Min(1),
+                    ])
+                    .split(chunks[2]);
+                self.draw_legend(f, chunks[0]);
+                self.draw_assist(f, assistant, chunks[1]);
+            }
+            None => self.draw_legend(f, chunks[2]),
+        }
+
+        if let (Some(row), None) = (current_rect, &self.pending_guess) {
+            self.draw_turn_time(f, Rect::new(chunks[2].x, row.y, chunks[2].width, 1));
+        }
+    }
+
+    /// Draws the countdown of the turn next to the current row, over the
+    /// legend if they meet
+    fn draw_turn_time(&self, f: &mut Frame<Backend>, area: Rect) {
+        let time_left = match self.turn_time_left() {
+            Some(time_left) if self.status() == State::Playing => time_left,
+            _ => return,
+        };
+        // round up so that 0:00 means the time is up
+        let time_left = time_left + Duration::from_secs(1) - Duration::from_nanos(1);
+        let mut style = self.theme.text;
+        if time_left <= TURN_TIME_WARNING {
+            style = style.modifier(Modifier::REVERSED);
+        }
+        let text = [Text::styled(format_duration(time_left), style)];
+        let area = Rect::new(area.x, area.y, area.width.min(TURN_TIME_WIDTH), 1);
+        f.render_widget(Clear, area);
+        f.render_widget(self.paragraph(text.iter()), area);
+    }
+
+    fn draw_assist(&self, f: &mut Frame<Backend>, assistant: &Assistant, area: Rect) {
+        let mut text = Vec::new();
+        if !assistant.is_available() {
+            text.push(Text::raw("Too many codes to analyze"));
+        } else if let Some(analysis) = assistant.analysis(self.guesses.len()) {
+            text.push(Text::raw("Suggestions\n"));
+            for suggestion in &analysis.suggestions {
+                text.extend(self.code_text(&suggestion.guess));
+                text.push(Text::raw(format!(
+                    "  {:.2} bits  {:.1} left\n",
+                    suggestion.score.entropy, suggestion.score.expected_remaining
+                )));
+            }
         } else {
-            &self.solution
+            text.push(Text::raw("Analyzing..."));
+        }
+        f.render_widget(self.paragraph(text.iter()), area);
+    }
+
+    /// Draws the rating of each guess next to its row
+    fn draw_review(
+        &self,
+        f: &mut Frame<Backend>,
+        reviewer: &Reviewer,
+        first_row: usize,
+        rows: &[Rect],
+        area: Rect,
+    ) {
+        let line = |row: &Rect| Rect::new(area.x, row.y, area.width, 1);
+
+        let reviews = match reviewer.reviews() {
+            Some(reviews) => reviews,
+            None => {
+                let text = [Text::raw(if reviewer.is_available() {
+                    "Analyzing..."
+                } else {
+                    "Too many codes to analyze"
+                })];
+                f.render_widget(self.paragraph(text.iter()), line(&rows[0]));
+                return;
+            }
         };
-        self.draw_row(f, &solution, None, solution_row);
 
-        let rows = rows.iter().skip(1).rev();
-        for (guess, hint, row) in izip!(guesses, hints, rows) {
-            self.draw_row(f, guess, Some(hint), *row);
+        let text = [Text::raw("Rating      Bits (best)  Better guess")];
+        f.render_widget(self.paragraph(text.iter()), line(&rows[0]));
+
+        for (review, row) in reviews
+            .iter()
+            .skip(first_row)
+            .zip(rows.iter().skip(1).rev())
+        {
+            let review = match review {
+                Some(review) => review,
+                None => {
+                    let text = [Text::raw("forfeited")];
+                    f.render_widget(self.paragraph(text.iter()), line(row));
+                    continue;
+                }
+            };
+            let mut text = vec![Text::raw(format!(
+                "{:10}  {:.2} ({:.2})  ",
+                review.rating, review.score.entropy, review.best.score.entropy
+            ))];
+            if review.rating != Rating::Best {
+                text.extend(self.code_text(&review.best.guess));
+            }
+            f.render_widget(self.paragraph(text.iter()), line(row));
+        }
+    }
+
+    /// What code pegs are called in the variant
+    fn peg_noun(&self) -> &'static str {
+        if self.settings.digits {
+            "digit"
+        } else {
+            "color"
+        }
+    }
+
+    /// Name of the color in messages
+    fn color_name(&self, color: usize) -> String {
+        if self.settings.blank() == Some(color) {
+            "blank".to_owned()
+        } else if self.settings.digits {
+            format!("digit {}", color)
+        } else {
+            self.theme.color_name(color)
+        }
+    }
+
+    /// Style of a code peg. Digits are told apart by themselves.
+    fn peg_style(&self, color: usize) -> Style {
+        if self.settings.blank() == Some(color) {
+            self.theme.empty
+        } else if self.settings.digits {
+            self.theme.text
+        } else {
+            Style::default().fg(self.theme.pegs[color])
+        }
+    }
+
+    /// Glyph of a code peg, which differs from an empty hole for the blank
+    fn peg_glyph(&self, color: usize) -> Cow<'static, str> {
+        if self.settings.blank() == Some(color) {
+            self.glyphs.blank.into()
+        } else {
+            self.glyphs.code(color)
         }
+    }
+
+    /// Colored pegs of a code, separated by spaces
+    fn code_text<'t>(&self, guess: &Guess) -> Vec<Text<'t>> {
+        Itertools::intersperse(
+            guess
+                .0
+                .iter()
+                .map(|c| Text::styled(self.peg_glyph(*c), self.peg_style(*c)))
+                .chain(iter::repeat(Text::styled(
+                    self.glyphs.empty,
+                    self.theme.empty,
+                )))
+                .take(self.settings.holes.get()),
+            Text::raw(" "),
+        )
+        .collect()
+    }
 
-        self.draw_legend(f, chunks[2]);
+    /// Most likely colors of the solution, if they are to be shown
+    fn overlay(&self) -> Option<&[(usize, f64)]> {
+        if !self.show_overlay {
+            return None;
+        }
+        let analysis = self.assistant.as_ref()?.analysis(self.guesses.len())?;
+        Some(&analysis.likely_colors)
     }
 
-    fn draw_row(&self, f: &mut Frame<Backend>, guess: &Guess, hint: Option<&Hint>, area: Rect) {
+    /// Draws the most likely color of each hole, brighter when more likely
+    fn draw_overlay(
+        &self,
+        f: &mut Frame<Backend>,
+        layout: &BoardLayout,
+        likely_colors: &[(usize, f64)],
+        area: Rect,
+    ) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Length(self.opt.holes.get() as u16 + 2),
-                Constraint::Min(1),
-            ])
+            .constraints([Constraint::Length(layout.hint_width()), Constraint::Min(1)])
             .split(area);
 
-        if let Some(hint) = hint {
-            let bulls = iter::repeat(Text::styled(CIRCLE, Style::default().fg(BULL_COLOR)))
-                .take(hint.bulls);
-            let cows =
-                iter::repeat(Text::styled(CIRCLE, Style::default().fg(COW_COLOR))).take(hint.cows);
-            let dots = iter::repeat(Text::raw(DOT));
+        let text: Vec<_> = Itertools::intersperse(
+            likely_colors.iter().map(|(color, probability)| {
+                let style = self.peg_style(*color);
+                let style = if *probability >= 1.0 {
+                    style.modifier(Modifier::BOLD)
+                } else if *probability >= 0.5 {
+                    style
+                } else {
+                    style.modifier(Modifier::DIM)
+                };
+                Text::styled(self.peg_glyph(*color), style)
+            }),
+            Text::raw(" "),
+        )
+        .collect();
+        f.render_widget(self.paragraph(text.iter()), chunks[1]);
+    }
 
-            let text: Vec<_> = bulls
-                .chain(cows)
-                .chain(dots)
-                .take(self.opt.holes.get())
-                .collect();
-            f.render_widget(Paragraph::new(text.iter()), chunks[0]);
-        }
+    fn draw_row(
+        &self,
+        f: &mut Frame<Backend>,
+        layout: &BoardLayout,
+        guess: &Guess,
+        hint: Option<&Hint>,
+        cursor: Option<usize>,
+        area: Rect,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(layout.hint_width()), Constraint::Min(1)])
+            .split(area);
 
-        let text: Vec<_> = guess
-            .0
-            .iter()
-            .map(|c| Text::styled(CIRCLE, Style::default().fg(CODE_COLORS[*c])))
-            .chain(iter::repeat(Text::raw(DOT)))
-            .take(self.opt.holes.get())
-            .intersperse(Text::raw(" "))
+        if let Some(hint) = hint {
+            let (bulls, cows) = if self.settings.blind {
+                (0, hint.bulls + hint.cows)
+            } else {
+                (hint.bulls, hint.cows)
+            };
+            let bulls = iter::repeat_n(
+                Text::styled(self.glyphs.bull, Style::default().fg(self.theme.bull)),
+                bulls,
+            );
+            let cows = iter::repeat_n(
+                Text::styled(self.glyphs.cow, Style::default().fg(self.theme.cow)),
+                cows,
+            );
+            let dots = iter::repeat(Text::styled(self.glyphs.empty, self.theme.empty));
+
+            let text: Vec<_> = Itertools::intersperse(
+                bulls
+                    .chain(cows)
+                    .chain(dots)
+                    .take(self.settings.holes.get())
+                    .chunks(layout.hint_pegs_per_line())
+                    .into_iter()
+                    .map(|line| line.collect::<Vec<_>>()),
+                vec![Text::raw("\n")],
+            )
+            .flatten()
             .collect();
-        f.render_widget(Paragraph::new(text.iter()), chunks[1]);
+            f.render_widget(self.paragraph(text.iter()), chunks[0]);
+        }
+
+        let mut text = self.code_text(guess);
+        // pegs are interspersed with spaces
+        if let Some(Text::Styled(_, style)) = cursor.and_then(|i| text.get_mut(i * 2)) {
+            *style = style.modifier(Modifier::REVERSED);
+        }
+        f.render_widget(self.paragraph(text.iter()), chunks[1]);
     }
 
     fn draw_legend(&self, f: &mut Frame<Backend>, area: Rect) {
+        let abbreviations = self.input.mode() != InputMode::Numbers;
         let chunks = Layout::default()
-            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(abbreviations as u16),
+                Constraint::Length(1),
+                Constraint::Min(1),
+            ])
             .split(area);
 
-        let text: Vec<_> = (0..self.opt.colors.get())
-            .map(|i| Text::raw((i + 1).to_string()))
-            .intersperse(Text::raw(" "))
+        // columns are as wide as the longest abbreviation
+        let width = self.legend_column_width();
+        let pad = |text: &str| format!("{:width$}", text, width = width);
+        let text: Vec<_> = Itertools::intersperse(
+            (0..self.settings.code_colors())
+                .map(|i| Text::raw(pad(&self.keys.color_key(i, &self.settings)))),
+            Text::raw(" "),
+        )
+        .collect();
+        f.render_widget(self.paragraph(text.iter()), chunks[0]);
+
+        // digits are the pegs themselves
+        if !self.settings.digits {
+            let text: Vec<_> = Itertools::intersperse(
+                (0..self.settings.code_colors())
+                    .map(|i| Text::styled(pad(&self.peg_glyph(i)), self.peg_style(i))),
+                Text::raw(" "),
+            )
             .collect();
-        f.render_widget(Paragraph::new(text.iter()), chunks[0]);
+            f.render_widget(self.paragraph(text.iter()), chunks[1]);
+        }
 
-        let text: Vec<_> = CODE_COLORS
-            .iter()
-            .take(self.opt.colors.get())
-            .map(|color| Text::styled(CIRCLE, Style::default().fg(*color)))
-            .intersperse(Text::raw(" "))
+        if abbreviations {
+            let text: Vec<_> = Itertools::intersperse(
+                (0..self.settings.code_colors())
+                    .map(|i| Text::raw(pad(&self.input.abbreviation(i).unwrap_or_default()))),
+                Text::raw(" "),
+            )
             .collect();
-        f.render_widget(Paragraph::new(text.iter()), chunks[1]);
+            f.render_widget(self.paragraph(text.iter()), chunks[2]);
+        }
+
+        let text: Vec<_> = self
+            .info_lines()
+            .into_iter()
+            .map(|line| Text::raw(line + "\n"))
+            .collect();
+        f.render_widget(self.paragraph(text.iter()), chunks[4]);
+    }
+
+    /// Width of a color in the legend, which fits its key and abbreviation
+    fn legend_column_width(&self) -> usize {
+        (0..self.settings.code_colors())
+            .filter_map(|color| self.input.abbreviation(color))
+            .map(|abbreviation| abbreviation.chars().count())
+            .max()
+            .unwrap_or(1)
+            .max(1)
+    }
+
+    /// Lines shown below the legend
+    fn info_lines(&self) -> Vec<String> {
+        self.ruleset_name
+            .iter()
+            .map(|name| format!("Rules: {}", name))
+            .chain(
+                self.daily
+                    .map(|day| format!("Daily #{}", daily::puzzle_number(day))),
+            )
+            .chain(
+                self.hints_withheld()
+                    .then(|| format!("Hints after guess {}", self.settings.guesses.get() - 1)),
+            )
+            .chain(
+                self.can_count_codes()
+                    .then(|| match self.counter.count(&self.settings) {
+                        Some(1) => "1 code left".to_owned(),
+                        Some(count) => format!("{} codes left", count),
+                        None => "Too many codes to count".to_owned(),
+                    }),
+            )
+            .collect()
     }
+
+    fn legend_width(&self) -> u16 {
+        let colors_width = self.settings.code_colors() * (self.legend_column_width() + 1) - 1;
+        let info_width = self
+            .info_lines()
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        colors_width.max(info_width) as u16
+    }
+}
+
+fn generate_solution(settings: &Settings, seed: u64) -> Guess {
+    // the daily puzzle needs the same code from the same seed everywhere
+    let mut rng = daily::SplitMix64::new(seed);
+    let holes = settings.holes.get();
+    let solution = if settings.no_duplicate {
+        // sample without replacement by shuffling the first holes
+        let mut colors: Vec<_> = (0..settings.code_colors()).collect();
+        for i in 0..holes {
+            let j = i + rng.below(colors.len() - i);
+            colors.swap(i, j);
+        }
+        colors.truncate(holes);
+        colors
+    } else {
+        // sample with replacement
+        (0..holes)
+            .map(|_| rng.below(settings.code_colors()))
+            .collect()
+    };
+
+    Guess(solution)
+}
+
+/// Reads terminal events in the background
+fn spawn_input_thread() -> Receiver<Event> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    std::thread::spawn(move || loop {
+        if let Ok(event) = event::read() {
+            let _ = tx.send(event);
+        }
+    });
+    rx
 }
 
 fn setup_terminal() -> Result<Terminal<Backend>> {
@@ -410,6 +2901,17 @@ fn setup_terminal() -> Result<Terminal<Backend>> {
     Ok(terminal)
 }
 
+/// Sets the terminal up again after another program used it, drawing
+/// everything anew
+fn restore_terminal(terminal: &mut Terminal<Backend>) -> Result<()> {
+    terminal::enable_raw_mode()?;
+    terminal.hide_cursor()?;
+    let area = terminal.size()?;
+    terminal.resize(area)?;
+
+    Ok(())
+}
+
 fn cleanup_terminal(terminal: &mut Terminal<Backend>) -> Result<()> {
     terminal.show_cursor()?;
     terminal::disable_raw_mode()?;
@@ -417,15 +2919,6 @@ fn cleanup_terminal(terminal: &mut Terminal<Backend>) -> Result<()> {
     Ok(())
 }
 
-fn parse_color_number(c: char) -> Option<usize> {
-    if let Some(digit) = c.to_digit(10) {
-        if digit != 0 {
-            return Some((digit - 1) as usize);
-        }
-    }
-    None
-}
-
 fn calc_hint(guess: &Guess, solution: &Guess, num_colors: usize) -> Hint {
     let mut bulls = 0;
     let mut guess_counts = vec![0usize; num_colors];
@@ -454,6 +2947,7 @@ extern crate quickcheck_macros;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crossterm::event::{KeyEvent, KeyModifiers};
     use quickcheck::TestResult;
 
     #[allow(clippy::needless_range_loop)]
@@ -485,6 +2979,46 @@ mod tests {
         Hint { bulls, cows }
     }
 
+    #[test]
+    fn stable_daily_code() {
+        // everyone gets the same code for the first daily puzzle
+        let settings = Settings::from_iter(["codebreaker"]);
+        let code = generate_solution(&settings, daily::seed(18444, &settings));
+        assert_eq!(code, Guess(vec![5, 5, 0, 4]));
+
+        let settings = Settings::from_iter(["codebreaker", "--no-duplicate"]);
+        let code = generate_solution(&settings, daily::seed(18444, &settings));
+        assert_eq!(code, Guess(vec![4, 0, 1, 5]));
+    }
+
+    #[test]
+    fn restore_finished_daily() {
+        let settings = Settings::from_iter(["codebreaker", "--time-limit", "60"]);
+        let entry = |gave_up, elapsed| daily::Entry {
+            day: 20000,
+            settings: settings.clone(),
+            guesses: vec![Guess(vec![0, 1, 2, 3]), Guess(vec![4, 5, 0, 1])],
+            gave_up,
+            elapsed,
+        };
+
+        // rerun without the time limit
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.restore_daily(entry(true, 30.0));
+        assert!(game.status() == State::Lost);
+        assert!(game.gave_up);
+        assert_eq!(
+            game.hints[1],
+            calc_hint(&game.guesses[1], &game.solution, 6)
+        );
+
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.restore_daily(entry(false, 60.0));
+        assert!(game.status() == State::Lost);
+        assert!(game.is_time_up());
+        assert!(!game.gave_up);
+    }
+
     #[quickcheck]
     fn hint(xs: Vec<(usize, usize)>) -> TestResult {
         if xs.is_empty() {
@@ -502,4 +3036,187 @@ mod tests {
 
         TestResult::passed()
     }
+
+    #[test]
+    fn digits_variant() {
+        let mut settings = Settings::from_iter(["codebreaker", "--digits", "--holes", "3"]);
+        settings.resolve_variant();
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.colors.get(), DIGITS);
+        assert!(settings.no_duplicate);
+
+        assert_eq!(settings.number_color(0), Some(0));
+        assert_eq!(settings.number_color(9), Some(9));
+        assert_eq!(settings.color_number(3), 3);
+        assert_eq!(settings.color_range(), "0-9");
+
+        let settings = Settings::default();
+        assert_eq!(settings.number_color(0), None);
+        assert_eq!(settings.number_color(6), Some(5));
+        assert_eq!(settings.color_range(), "1-6");
+    }
+
+    #[test]
+    fn blank_pegs() {
+        let settings = Settings::from_iter(["codebreaker", "--allow-empty"]);
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.blank(), Some(6));
+        assert_eq!(settings.number_color(0), Some(6));
+        assert_eq!(settings.number_color(7), None);
+        assert_eq!(settings.color_number(6), 0);
+        assert_eq!(settings.color_range(), "0-6");
+
+        let hint = calc_hint(&Guess(vec![6, 6, 0, 1]), &Guess(vec![6, 0, 6, 2]), 7);
+        assert_eq!(hint, Hint { bulls: 1, cows: 2 });
+        assert!((0..100)
+            .flat_map(|seed| generate_solution(&settings, seed).0)
+            .any(|color| color == 6));
+    }
+
+    #[test]
+    fn repeated_guess() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        for _ in 0..2 {
+            game.current_guess = Guess(vec![0, 1, 2, 3]);
+            assert!(game.submit().is_ok());
+        }
+        assert_eq!(game.notifications.len(), 1);
+
+        // strict is another name for hard, which rejects the repetition
+        let settings = Settings::from_iter(["codebreaker", "--strict"]);
+        assert!(settings.hard);
+        let mut game = Game::with_seed(settings, 0);
+        for _ in 0..2 {
+            game.current_guess = Guess(vec![0, 1, 2, 3]);
+            game.on_enter();
+        }
+        assert_eq!(game.guesses.len(), 1);
+    }
+
+    #[test]
+    fn turn_time() {
+        let settings = Settings::from_iter(["codebreaker", "--turn-time", "10", "--no-duplicate"]);
+        let mut game = Game::with_seed(settings, 0);
+        game.place(0);
+        game.check_turn_time();
+        assert!(game.guesses.is_empty());
+
+        // the turn runs out with the first peg placed
+        game.stopwatch = Stopwatch::with_elapsed(Duration::from_secs(10));
+        game.check_turn_time();
+        assert_eq!(game.guesses.len(), 1);
+        let guess = &game.guesses[0].0;
+        assert_eq!(guess[0], 0);
+        assert_eq!(guess.iter().unique().count(), 4);
+        assert_eq!(game.turn_time_left(), Some(Duration::from_secs(10)));
+
+        game.settings.forfeit_turn = true;
+        game.stopwatch = Stopwatch::with_elapsed(Duration::from_secs(20));
+        game.check_turn_time();
+        assert_eq!(game.guesses[1], Guess::default());
+        assert_eq!(game.hints[1], Hint::default());
+    }
+
+    #[test]
+    fn pause() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.begin();
+        game.pause();
+        let elapsed = game.stopwatch.elapsed();
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(game.stopwatch.elapsed(), elapsed);
+
+        game.resume();
+        assert!(!game.paused);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(game.stopwatch.elapsed() > elapsed);
+    }
+
+    #[test]
+    fn keys_of_actions_in_initials_mode() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.set_input_mode(InputMode::Initials);
+
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        assert!(!game.input.takes(g));
+        assert_eq!(game.keys.action(g, false), Some(KeyAction::GiveUp));
+        // green takes the next letter of its name
+        let green = (0..game.settings.colors.get())
+            .position(|color| game.color_name(color) == "green")
+            .unwrap();
+        assert_eq!(game.input.abbreviation(green), Some("e".to_owned()));
+    }
+
+    #[test]
+    fn edit_with_cursor() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        for color in &[0, 1, 2] {
+            game.place(*color);
+        }
+        game.move_cursor(true);
+        game.move_cursor(true);
+        game.place(3);
+        assert_eq!(game.current_guess, Guess(vec![0, 3, 2]));
+        assert_eq!(game.cursor(), 2);
+
+        game.toggle_insert();
+        game.place(4);
+        assert_eq!(game.current_guess, Guess(vec![0, 3, 4, 2]));
+        game.place(5);
+        assert_eq!(game.current_guess, Guess(vec![0, 3, 4, 2]));
+
+        game.on_delete();
+        assert_eq!(game.current_guess, Guess(vec![0, 3, 4]));
+        assert_eq!(game.cursor(), 3);
+        game.on_delete();
+        assert_eq!(game.current_guess, Guess(vec![0, 3, 4]));
+
+        game.move_cursor(true);
+        game.on_backspace();
+        assert_eq!(game.current_guess, Guess(vec![0, 4]));
+        assert_eq!(game.cursor(), 1);
+        game.move_cursor(false);
+        game.move_cursor(false);
+        assert_eq!(game.cursor(), 2);
+    }
+
+    #[test]
+    fn restart() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.current_guess = Guess(vec![0, 1, 2, 3]);
+        game.on_enter();
+        game.give_up();
+        assert!(game.status() == State::Lost);
+
+        assert!(game.check_new_game().is_ok());
+        game.restart();
+        assert!(game.status() == State::Playing);
+        assert!(game.guesses.is_empty());
+        assert_eq!(game.solution, generate_solution(&game.settings, game.seed));
+
+        game.daily = Some(0);
+        assert!(game.check_new_game().is_err());
+    }
+
+    #[test]
+    fn static_hints() {
+        let settings = Settings::from_iter(["codebreaker", "--static", "--guesses", "3"]);
+        let mut game = Game::with_seed(settings, 0);
+        let guess = |game: &mut Game, code: Guess| {
+            game.current_guess = code;
+            game.submit().unwrap();
+        };
+
+        // cracked, but not told until the hints are revealed
+        let solution = game.solution.clone();
+        guess(&mut game, solution);
+        assert!(game.status() == State::Playing);
+        assert!(game.are_hints_hidden());
+        guess(&mut game, Guess(vec![0, 0, 0, 0]));
+        assert!(!game.are_hints_hidden());
+        assert!(game.status() == State::Won);
+
+        let settings = Settings::from_iter(["codebreaker", "--blind", "--hard"]);
+        assert!(settings.validate().is_err());
+    }
 }
@@ -0,0 +1,72 @@
+//! Rounds played back to back with --marathon, which go on until a code is
+//! not broken
+
+use crate::stopwatch::format_duration;
+use std::time::Duration;
+
+/// Progress through the rounds of a marathon
+#[derive(Debug, Default)]
+pub struct Marathon {
+    /// Codes broken in a row
+    pub streak: usize,
+    /// Longest streak under the rules, including the current one
+    pub best: usize,
+    /// Time spent on the broken codes
+    time: Duration,
+}
+
+impl Marathon {
+    /// Marathon under rules whose longest streak so far is `best`
+    pub fn new(best: usize) -> Self {
+        Self {
+            best,
+            ..Default::default()
+        }
+    }
+
+    /// Counts a broken code, which took `elapsed`
+    pub fn on_win(&mut self, elapsed: Duration) {
+        self.streak += 1;
+        self.best = self.best.max(self.streak);
+        self.time += elapsed;
+    }
+
+    /// Starts over after a round is lost or abandoned
+    pub fn reset(&mut self) {
+        self.streak = 0;
+        self.time = Duration::default();
+    }
+
+    /// e.g. "Streak 3  Best 5  Total 4:12", where `elapsed` is the time
+    /// spent on the current round
+    pub fn status(&self, elapsed: Duration) -> String {
+        format!(
+            "Streak {}  Best {}  Total {}",
+            self.streak,
+            self.best,
+            format_duration(self.time + elapsed)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streak() {
+        let mut marathon = Marathon::new(1);
+        marathon.on_win(Duration::from_secs(30));
+        marathon.on_win(Duration::from_secs(40));
+        assert_eq!(marathon.best, 2);
+        assert_eq!(
+            marathon.status(Duration::from_secs(5)),
+            "Streak 2  Best 2  Total 1:15"
+        );
+
+        marathon.reset();
+        marathon.on_win(Duration::from_secs(10));
+        assert_eq!(marathon.streak, 1);
+        assert_eq!(marathon.best, 2);
+    }
+}
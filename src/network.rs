@@ -0,0 +1,382 @@
+use crate::keymap::KeyAction;
+use crate::{
+    calc_hint, setup_terminal, spawn_input_thread, Game, Guess, Hint, Settings, State,
+    TICK_INTERVAL,
+};
+use anyhow::{Context, Result};
+use crossbeam_channel::{Receiver, Sender};
+use crossterm::event::{Event, KeyCode};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// Longest message accepted, to keep a broken peer from exhausting memory
+const MAX_MESSAGE_LEN: u32 = 64 * 1024;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Messages exchanged between the codemaker (host) and the codebreaker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Message {
+    /// Sent by the host once connected
+    Hello { settings: Settings },
+    /// Sent by the codebreaker
+    Guess { guess: Guess },
+    /// Reply to a guess. The solution is revealed with the last hint.
+    Hint { hint: Hint, solution: Option<Guess> },
+}
+
+/// Each message is a big-endian 32-bit length followed by JSON
+fn write_message(mut writer: impl Write, message: &Message) -> Result<()> {
+    let json = serde_json::to_vec(message)?;
+    writer.write_all(&(json.len() as u32).to_be_bytes())?;
+    writer.write_all(&json)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn read_message(mut reader: impl Read) -> Result<Message> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_MESSAGE_LEN {
+        return Err(anyhow::anyhow!("message too long ({} bytes)", len));
+    }
+
+    let mut json = vec![0; len as usize];
+    reader.read_exact(&mut json)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Connection to the other player. Messages are received in the background
+/// so that the TUI keeps responding.
+pub struct Connection {
+    stream: TcpStream,
+    rx: Receiver<Result<Message>>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self> {
+        let mut reader = stream.try_clone()?;
+        let (tx, rx) = crossbeam_channel::unbounded();
+        std::thread::spawn(move || loop {
+            let message = read_message(&mut reader);
+            let failed = message.is_err();
+            if tx.send(message).is_err() || failed {
+                break;
+            }
+        });
+
+        Ok(Self { stream, rx })
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<()> {
+        write_message(&mut self.stream, message)
+    }
+
+    /// Yields an error once when the connection is lost
+    pub fn receiver(&self) -> &Receiver<Result<Message>> {
+        &self.rx
+    }
+
+    pub fn peer(&self) -> String {
+        self.stream
+            .peer_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|_| "the other player".to_owned())
+    }
+}
+
+/// Connects to the host and returns the game to play as the codebreaker.
+/// The solution is unknown until the host reveals it.
+pub fn join(address: &str) -> Result<Game> {
+    let addrs: Vec<_> = address
+        .to_socket_addrs()
+        .with_context(|| format!("invalid address {}", address))?
+        .collect();
+    let stream = addrs
+        .iter()
+        .find_map(|addr| TcpStream::connect_timeout(addr, CONNECT_TIMEOUT).ok())
+        .ok_or_else(|| anyhow::anyhow!("failed to connect to {}", address))?;
+
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    let settings = match read_message(&stream).context("failed to receive the rules")? {
+        Message::Hello { settings } => settings,
+        message => return Err(anyhow::anyhow!("unexpected message {:?}", message)),
+    };
+    settings.validate()?;
+    stream.set_read_timeout(None)?;
+
+    let mut game = Game::new(settings);
+    game.solution = Guess::default();
+    game.remote = Some(Connection::new(stream)?);
+    Ok(game)
+}
+
+/// Sends the current row to the host as the codebreaker
+pub fn send_guess(game: &mut Game) {
+    if game.pending_guess.is_some() {
+        game.notifications
+            .push("Wait for the hint to the previous guess");
+        return;
+    }
+    if let Err(message) = game.check_guess() {
+        game.notifications.push(message);
+        return;
+    }
+
+    let guess = std::mem::take(&mut game.current_guess);
+    let message = Message::Guess {
+        guess: guess.clone(),
+    };
+    if let Some(remote) = &mut game.remote {
+        match remote.send(&message) {
+            Ok(()) => game.pending_guess = Some(guess),
+            Err(err) => {
+                game.current_guess = guess;
+                game.notifications
+                    .push(format!("Failed to send the guess: {}", err));
+            }
+        }
+    }
+}
+
+/// Handles a message from the host as the codebreaker
+pub fn on_message(game: &mut Game, message: Message) -> Result<()> {
+    let (hint, solution) = match message {
+        Message::Hint { hint, solution } => (hint, solution),
+        message => return Err(anyhow::anyhow!("unexpected message {:?}", message)),
+    };
+    let guess = game
+        .pending_guess
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("received a hint without a guess"))?;
+    if hint.bulls + hint.cows > game.settings.holes.get() {
+        return Err(anyhow::anyhow!("invalid hint"));
+    }
+
+    if let Some(solution) = solution {
+        if !is_valid_code(&game.settings, &solution) {
+            return Err(anyhow::anyhow!("invalid solution"));
+        }
+        game.solution = solution;
+    }
+    game.add_guess(guess, hint);
+
+    Ok(())
+}
+
+fn is_valid_code(settings: &Settings, code: &Guess) -> bool {
+    code.0.len() == settings.holes.get()
+        && code.0.iter().all(|&c| c < settings.code_colors())
+        && !(settings.no_duplicate && (1..code.0.len()).any(|i| code.0[..i].contains(&code.0[i])))
+}
+
+/// Listens for a codebreaker and gives hints to its guesses. The host first
+/// enters the secret code.
+pub fn host(game: &mut Game, port: u16) -> Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .with_context(|| format!("failed to listen on port {}", port))?;
+
+    let input_rx = spawn_input_thread();
+    let mut terminal = setup_terminal()?;
+    let ticker = crossbeam_channel::tick(TICK_INTERVAL);
+
+    let (connection_tx, connection_rx) = crossbeam_channel::bounded::<io::Result<TcpStream>>(1);
+    let mut listener = Some(listener);
+    let mut remote: Option<Connection> = None;
+    let mut finished = false;
+    let mut connection_lost = false;
+
+    loop {
+        game.caption = Some(if !game.show_solution {
+            "Enter the secret code and press enter".to_owned()
+        } else if let Some(remote) = &remote {
+            format!("Playing with {}", remote.peer())
+        } else {
+            format!("Waiting for a codebreaker on port {}", port)
+        });
+
+        let timeout = match game.notifications.update(Instant::now()) {
+            Some(duration) => crossbeam_channel::after(duration),
+            None => crossbeam_channel::never(),
+        };
+
+        terminal.draw(|mut f| {
+            game.draw(&mut f);
+        })?;
+
+        let remote_rx = match &remote {
+            Some(remote) => remote.receiver().clone(),
+            None => crossbeam_channel::never(),
+        };
+        let event = crossbeam_channel::select! {
+            recv(input_rx) -> event => Some(event?),
+            recv(connection_rx) -> stream => {
+                match stream?.map_err(anyhow::Error::from).and_then(|stream| greet(game, stream)) {
+                    Ok(connection) => remote = Some(connection),
+                    Err(err) => game
+                        .notifications
+                        .push(format!("Failed to accept a codebreaker: {}", err)),
+                }
+                None
+            }
+            recv(remote_rx) -> message => {
+                let result = message?.and_then(|message| answer(game, &mut remote, message));
+                if let Err(err) = result {
+                    game.notifications.push(format!("Connection lost: {}", err));
+                    connection_lost = true;
+                }
+                None
+            }
+            recv(timeout) -> _ => None,
+            recv(ticker) -> _ => None,
+        };
+
+        if game.status() != State::Playing {
+            finished = true;
+            break;
+        }
+        if connection_lost {
+            break;
+        }
+
+        if let Some(Event::Key(key)) = event {
+            match (game.keys.action(key, false), key.code) {
+                (Some(KeyAction::Quit), _) => break,
+                _ if game.show_solution => (),
+                (Some(KeyAction::Undo), _) => game.on_backspace(),
+                (Some(KeyAction::Delete), _) => game.on_delete(),
+                (Some(KeyAction::CursorLeft), _) => game.move_cursor(true),
+                (Some(KeyAction::CursorRight), _) => game.move_cursor(false),
+                (Some(KeyAction::Insert), _) => game.toggle_insert(),
+                (Some(KeyAction::Submit), _) => match game.check_guess() {
+                    Ok(()) => {
+                        game.solution = std::mem::take(&mut game.current_guess);
+                        game.show_solution = true;
+                        if let Some(listener) = listener.take() {
+                            listen(listener, connection_tx.clone());
+                        }
+                    }
+                    Err(message) => game.notifications.push(message),
+                },
+                (None, KeyCode::Char(c)) => game.on_char(c),
+                _ => (),
+            }
+        }
+    }
+
+    game.caption = Some(match (finished, game.status()) {
+        (true, State::Won) => format!(
+            "The codebreaker cracked the code in {} guesses",
+            game.guesses.len()
+        ),
+        (true, _) => "The codebreaker failed to crack the code".to_owned(),
+        (false, _) => "The game was interrupted".to_owned(),
+    });
+    game.close(terminal)
+}
+
+/// Sends the rules to the codebreaker
+fn greet(game: &Game, stream: TcpStream) -> Result<Connection> {
+    let mut connection = Connection::new(stream)?;
+    connection.send(&Message::Hello {
+        settings: game.settings.clone(),
+    })?;
+    Ok(connection)
+}
+
+/// Accepts a single codebreaker in the background
+fn listen(listener: TcpListener, tx: Sender<io::Result<TcpStream>>) {
+    std::thread::spawn(move || {
+        let _ = tx.send(listener.accept().map(|(stream, _)| stream));
+    });
+}
+
+/// Replies to a guess of the codebreaker
+fn answer(game: &mut Game, remote: &mut Option<Connection>, message: Message) -> Result<()> {
+    let guess = match message {
+        Message::Guess { guess } if is_valid_code(&game.settings, &guess) => guess,
+        message => return Err(anyhow::anyhow!("unexpected message {:?}", message)),
+    };
+
+    let hint = calc_hint(&guess, &game.solution, game.settings.code_colors());
+    game.add_guess(guess, hint.clone());
+
+    let solution = if game.status() == State::Playing {
+        None
+    } else {
+        Some(game.solution.clone())
+    };
+    if let Some(remote) = remote {
+        remote.send(&Message::Hint { hint, solution })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_prefixed() {
+        let message = Message::Guess {
+            guess: Guess(vec![0, 1, 2, 3]),
+        };
+        let mut buf = Vec::new();
+        write_message(&mut buf, &message).unwrap();
+        assert_eq!(
+            u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize,
+            buf.len() - 4
+        );
+        assert_eq!(read_message(&buf[..]).unwrap(), message);
+
+        assert!(read_message(&buf[..buf.len() - 1]).is_err());
+        assert!(read_message(&u32::MAX.to_be_bytes()[..]).is_err());
+    }
+
+    #[test]
+    fn play_over_loopback() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let mut host = Game::with_seed(Settings::default(), 0);
+        host.solution = Guess(vec![0, 1, 2, 3]);
+        let settings = host.settings.clone();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut remote = Some(Connection::new(stream).unwrap());
+            let connection = remote.as_mut().unwrap();
+            connection.send(&Message::Hello { settings }).unwrap();
+            for _ in 0..2 {
+                let message = remote.as_ref().unwrap().receiver().recv().unwrap().unwrap();
+                answer(&mut host, &mut remote, message).unwrap();
+            }
+            host.guesses.len()
+        });
+
+        let mut game = join(&address).unwrap();
+        for guess in [vec![0, 1, 3, 2], vec![0, 1, 2, 3]] {
+            game.current_guess = Guess(guess);
+            send_guess(&mut game);
+            let message = game
+                .remote
+                .as_ref()
+                .unwrap()
+                .receiver()
+                .recv()
+                .unwrap()
+                .unwrap();
+            on_message(&mut game, message).unwrap();
+        }
+
+        assert_eq!(server.join().unwrap(), 2);
+        assert_eq!(game.hints[0], Hint { bulls: 2, cows: 2 });
+        assert!(game.status() == State::Won);
+        assert_eq!(game.solution, Guess(vec![0, 1, 2, 3]));
+    }
+}
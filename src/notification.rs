@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a notification stays on screen
+const DURATION: Duration = Duration::from_secs(3);
+
+/// Maximum number of notifications shown at once
+pub const MAX_VISIBLE: usize = 3;
+
+struct Notification {
+    text: String,
+    /// Whether the status line flashes while it is shown
+    warning: bool,
+    /// When the notification appeared on screen
+    shown_at: Option<Instant>,
+}
+
+/// Queue of transient messages telling the player why an action was
+/// ignored or what happened in the background
+#[derive(Default)]
+pub struct Notifications {
+    queue: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.push_notification(text.into(), false);
+    }
+
+    /// Pushes a message about a likely mistake of the player
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.push_notification(text.into(), true);
+    }
+
+    fn push_notification(&mut self, text: String, warning: bool) {
+        // show the same message again instead of stacking up duplicates
+        if let Some(notification) = self.queue.iter_mut().find(|n| n.text == text) {
+            notification.shown_at = None;
+            return;
+        }
+
+        self.queue.push_back(Notification {
+            text,
+            warning,
+            shown_at: None,
+        });
+    }
+
+    /// Removes expired notifications and returns how long to wait until
+    /// the next one expires
+    pub fn update(&mut self, now: Instant) -> Option<Duration> {
+        self.queue.retain(|n| match n.shown_at {
+            Some(shown_at) => now.duration_since(shown_at) < DURATION,
+            None => true,
+        });
+
+        self.queue
+            .iter_mut()
+            .take(MAX_VISIBLE)
+            .map(|n| {
+                let shown_at = *n.shown_at.get_or_insert(now);
+                DURATION - now.duration_since(shown_at)
+            })
+            .min()
+    }
+
+    pub fn visible(&self) -> impl Iterator<Item = &str> {
+        self.queue.iter().take(MAX_VISIBLE).map(|n| n.text.as_str())
+    }
+
+    /// Whether a warning is on screen
+    pub fn has_visible_warning(&self) -> bool {
+        self.queue.iter().take(MAX_VISIBLE).any(|n| n.warning)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len().min(MAX_VISIBLE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue() {
+        let start = Instant::now();
+        let mut notifications = Notifications::default();
+        for i in 0..MAX_VISIBLE + 1 {
+            notifications.push(i.to_string());
+        }
+        assert_eq!(notifications.update(start), Some(DURATION));
+        assert_eq!(notifications.len(), MAX_VISIBLE);
+        assert_eq!(notifications.visible().next(), Some("0"));
+
+        // the hidden one appears after the others expire
+        let later = start + DURATION;
+        assert_eq!(notifications.update(later), Some(DURATION));
+        assert_eq!(notifications.visible().collect::<Vec<_>>(), vec!["3"]);
+
+        assert_eq!(notifications.update(later + DURATION), None);
+        assert_eq!(notifications.len(), 0);
+    }
+
+    #[test]
+    fn duplicate() {
+        let start = Instant::now();
+        let mut notifications = Notifications::default();
+        notifications.push("a");
+        notifications.update(start);
+
+        let later = start + DURATION / 2;
+        notifications.push("a");
+        assert_eq!(notifications.update(later), Some(DURATION));
+        assert_eq!(notifications.len(), 1);
+    }
+
+    #[test]
+    fn warning() {
+        let mut notifications = Notifications::default();
+        notifications.push("a");
+        assert!(!notifications.has_visible_warning());
+        notifications.warn("b");
+        assert!(notifications.has_visible_warning());
+    }
+}
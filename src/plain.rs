@@ -0,0 +1,236 @@
+//! Line-based frontend for screen readers, dumb terminals and serial
+//! consoles. The board is printed as text after each move, and guesses are
+//! read as lines of typed numbers.
+
+use crate::batch::parse_guess;
+use crate::stopwatch::format_duration;
+use crate::{save, share, Game, Guess, Hint, State};
+use anyhow::Result;
+use itertools::Itertools;
+use std::io::{BufRead, Write};
+
+const HELP: &str = "\
+Commands:
+  1 2 3 4  Make a guess, with or without spaces between the numbers
+  board    Show the board again
+  give up  Lose the game and show the code
+  help     Show this help
+  quit     Leave the game";
+
+/// Plays the game reading commands from `input` and writing the board to
+/// `output`, until the game is over or `input` ends
+pub fn play(game: &mut Game, input: impl BufRead, mut output: impl Write) -> Result<()> {
+    game.begin();
+    writeln!(output, "{}", intro(game))?;
+    writeln!(output, "Type help for the commands")?;
+    if !game.guesses.is_empty() {
+        print_board(game, &mut output)?;
+    }
+
+    let mut lines = input.lines();
+    while game.status() == State::Playing {
+        write!(
+            output,
+            "Guess {}/{}> ",
+            game.guesses.len() + 1,
+            game.settings.guesses
+        )?;
+        output.flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        // a line typed after the turn ran out comes too late for the turn
+        let guesses = game.guesses.len();
+        game.check_turn_time();
+        if game.guesses.len() != guesses {
+            writeln!(output, "Time is up for guess {}", guesses + 1)?;
+            print_board(game, &mut output)?;
+            continue;
+        }
+        match line.trim() {
+            "" => (),
+            "quit" | "q" => break,
+            "help" | "?" => writeln!(output, "{}", HELP)?,
+            "board" => print_board(game, &mut output)?,
+            "give up" => game.give_up(),
+            line => {
+                let result = parse_guess(game, line).and_then(|guess| {
+                    game.current_guess = guess;
+                    let result = game.submit();
+                    game.current_guess = Guess::default();
+                    result
+                });
+                match result {
+                    Ok(_) => print_board(game, &mut output)?,
+                    Err(message) => writeln!(output, "{}", message)?,
+                }
+            }
+        }
+    }
+
+    if game.status() == State::Playing {
+        // leaving keeps the game as quitting the TUI does
+        writeln!(output)?;
+        if let Some(path) = &game.save_path {
+            save::store(path, game)?;
+            writeln!(output, "Saved to {}", path.display())?;
+        }
+        return Ok(());
+    }
+
+    game.on_game_over();
+    writeln!(output, "{}", game.result_message())?;
+    if game.status() != State::Won {
+        writeln!(
+            output,
+            "The code was {}",
+            describe_code(game, &game.solution)
+        )?;
+    }
+    if let Some(stats) = &game.stats {
+        writeln!(output, "{}", stats.summary())?;
+    }
+    game.run_hook();
+    if game.print_share {
+        writeln!(
+            output,
+            "\n{}",
+            share::card(game, share::supports_emoji(&game.glyphs))
+        )?;
+    }
+    Ok(())
+}
+
+/// e.g. "Break the code of 4 holes in 8 guesses. Colors: 1 red, 2 green, ..."
+fn intro(game: &Game) -> String {
+    let settings = &game.settings;
+    if settings.digits {
+        return format!(
+            "Break the number of {} distinct digits in {} guesses.",
+            settings.holes, settings.guesses
+        );
+    }
+
+    let colors = (0..settings.code_colors())
+        .map(|color| {
+            format!(
+                "{} {}",
+                settings.color_number(color),
+                game.color_name(color)
+            )
+        })
+        .join(", ");
+    let duplicates = if settings.no_duplicate {
+        "No color appears twice"
+    } else {
+        "Colors may appear more than once"
+    };
+    format!(
+        "Break the code of {} holes in {} guesses. Colors: {}. {}.",
+        settings.holes, settings.guesses, colors, duplicates
+    )
+}
+
+/// Numbers of the colors, followed by their names in a game of colors
+fn describe_code(game: &Game, code: &Guess) -> String {
+    let numbers = code
+        .0
+        .iter()
+        .map(|&color| game.settings.color_number(color))
+        .join(" ");
+    if game.settings.digits {
+        return numbers;
+    }
+
+    let names = code
+        .0
+        .iter()
+        .map(|&color| game.color_name(color))
+        .join(", ");
+    format!("{} ({})", numbers, names)
+}
+
+/// e.g. "2 exact, 1 misplaced"
+fn describe_hint(game: &Game, hint: &Hint) -> String {
+    if game.settings.blind {
+        format!("{} correct", hint.bulls + hint.cows)
+    } else {
+        format!("{} exact, {} misplaced", hint.bulls, hint.cows)
+    }
+}
+
+fn print_board(game: &Game, output: &mut impl Write) -> Result<()> {
+    let hidden = game.are_hints_hidden();
+    for (i, (guess, hint)) in game.guesses.iter().zip(&game.hints).enumerate() {
+        if guess.0.is_empty() {
+            writeln!(output, "{}. forfeited", i + 1)?;
+            continue;
+        }
+        let hint = if hidden {
+            "hint hidden".to_owned()
+        } else {
+            describe_hint(game, hint)
+        };
+        writeln!(
+            output,
+            "{}. {}: {}",
+            i + 1,
+            describe_code(game, guess),
+            hint
+        )?;
+    }
+
+    if game.status() != State::Playing {
+        return Ok(());
+    }
+    let mut status = vec![match game.settings.guesses.get() - game.guesses.len() {
+        1 => "Last guess".to_owned(),
+        left => format!("{} guesses left", left),
+    }];
+    if game.hints_withheld() {
+        status.push(format!(
+            "hints after guess {}",
+            game.settings.guesses.get() - 1
+        ));
+    }
+    if game.can_count_codes() {
+        if let Some(count) = game.counter.count(&game.settings) {
+            status.push(format!("{} codes left", count));
+        }
+    }
+    if let Some(time_left) = game.time_left() {
+        status.push(format!("{} left", format_duration(time_left)));
+    }
+    if let Some(time_left) = game.turn_time_left() {
+        status.push(format!("{} for this guess", format_duration(time_left)));
+    }
+    writeln!(output, "{}", status.join(", "))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Settings;
+
+    #[test]
+    fn announce_hints() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        game.solution = Guess(vec![0, 1, 2, 3]);
+
+        let mut output = Vec::new();
+        // the game is left unfinished so that the statistics stay untouched
+        let input = "help\n1 3 2 4\n12\n";
+        play(&mut game, input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("Colors: 1 blue, 2 red, 3 green"));
+        assert!(output.contains("1. 1 3 2 4 (blue, green, red, yellow): 2 exact, 2 misplaced"));
+        assert!(output.contains("7 guesses left, "));
+        assert!(output.contains("Expected 4 colors, got 2"));
+        assert!(output.ends_with("Guess 2/8> \n"));
+        assert_eq!(game.guesses.len(), 1);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::keymap::KeyAction;
+use crate::stopwatch::{format_duration, Stopwatch};
+use crate::{setup_terminal, spawn_input_thread, Game, Settings, State, TICK_INTERVAL};
+use anyhow::{Context, Result};
+use crossterm::event::{Event, KeyCode};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Slowest and fastest playback speeds
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    /// Put the color at the cursor
+    Place(usize),
+    Undo,
+    CursorLeft,
+    CursorRight,
+    Delete,
+    ToggleInsert,
+    Submit,
+    GiveUp,
+    /// Lose the turn which ran out of time
+    Forfeit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct TimedAction {
+    /// Time on the game clock in milliseconds
+    time: u64,
+    action: Action,
+}
+
+/// Actions taken in a game, enough to reproduce it from scratch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    settings: Settings,
+    seed: u64,
+    /// Time on the game clock when the recording started, in milliseconds
+    start: u64,
+    /// Time on the game clock when the recording ended, in milliseconds
+    end: u64,
+    actions: Vec<TimedAction>,
+}
+
+impl Replay {
+    /// Starts recording the game. Guesses made so far are recorded as if they
+    /// were made at the start.
+    pub fn new(game: &Game) -> Self {
+        let start = game.stopwatch.elapsed().as_millis() as u64;
+        let mut replay = Self {
+            settings: game.settings.clone(),
+            seed: game.seed,
+            start,
+            end: start,
+            actions: Vec::new(),
+        };
+
+        for guess in &game.guesses {
+            // forfeited turns are all empty rows
+            if guess.0.is_empty() {
+                replay.push(game.stopwatch.elapsed(), Action::Forfeit);
+                continue;
+            }
+            for &color in &guess.0 {
+                replay.push(game.stopwatch.elapsed(), Action::Place(color));
+            }
+            replay.push(game.stopwatch.elapsed(), Action::Submit);
+        }
+        for &color in &game.current_guess.0 {
+            replay.push(game.stopwatch.elapsed(), Action::Place(color));
+        }
+
+        replay
+    }
+
+    pub fn push(&mut self, time: Duration, action: Action) {
+        let time = time.as_millis() as u64;
+        self.actions.push(TimedAction { time, action });
+        self.end = self.end.max(time);
+    }
+
+    /// Marks the end of the recording
+    pub fn finish(&mut self, time: Duration) {
+        self.end = self.end.max(time.as_millis() as u64);
+    }
+
+    /// Game in the state before the first action
+    pub fn game(&self) -> Game {
+        Game::with_seed(self.settings.clone(), self.seed)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.settings.validate()?;
+
+        if self.start > self.end {
+            return Err(anyhow::anyhow!("recording ends before it starts"));
+        }
+
+        let mut time = self.start;
+        for TimedAction { time: t, action } in &self.actions {
+            if *t < time || *t > self.end {
+                return Err(anyhow::anyhow!("actions are out of order"));
+            }
+            time = *t;
+
+            if let Action::Place(color) = action {
+                if *color >= self.settings.code_colors() {
+                    return Err(anyhow::anyhow!("color out of range"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn store(path: &Path, replay: &Replay) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), replay)?;
+
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<Replay> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let replay: Replay = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    replay
+        .validate()
+        .with_context(|| format!("invalid replay {}", path.display()))?;
+
+    Ok(replay)
+}
+
+/// Performs the recorded action on the game
+fn perform(game: &mut Game, action: Action) {
+    match action {
+        Action::Place(color) => game.place(color),
+        Action::Undo => game.on_backspace(),
+        Action::CursorLeft => game.move_cursor(true),
+        Action::CursorRight => game.move_cursor(false),
+        Action::Delete => game.on_delete(),
+        Action::ToggleInsert => game.toggle_insert(),
+        Action::Submit => game.on_enter(),
+        Action::GiveUp => game.give_up(),
+        Action::Forfeit => game.forfeit_turn(),
+    }
+}
+
+/// Plays back the replay on the game returned by `Replay::game`
+pub fn play(game: &mut Game, replay: &Replay, speed: f64) -> Result<()> {
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(anyhow::anyhow!("--speed must be a positive number"));
+    }
+
+    let rx = spawn_input_thread();
+    let mut terminal = setup_terminal()?;
+
+    let ticker = crossbeam_channel::tick(TICK_INTERVAL);
+    let end = Duration::from_millis(replay.end);
+    let mut position = Duration::from_millis(replay.start);
+    let mut speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    let mut paused = false;
+    let mut next = 0;
+    let mut last_update = Instant::now();
+
+    loop {
+        let now = Instant::now();
+        if !paused {
+            position = (position + (now - last_update).mul_f64(speed)).min(end);
+        }
+        last_update = now;
+
+        while let Some(action) = replay.actions.get(next) {
+            if Duration::from_millis(action.time) > position {
+                break;
+            }
+            perform(game, action.action);
+            next += 1;
+        }
+        game.stopwatch = Stopwatch::with_elapsed(position);
+
+        if game.status() != State::Playing || position >= end {
+            break;
+        }
+
+        game.caption = Some(format!(
+            "Replay {} / {} at {}x{}  {}: pause  {}: step  +/-: speed",
+            format_duration(position),
+            format_duration(end),
+            speed,
+            if paused { " (paused)" } else { "" },
+            game.keys.key(KeyAction::Pause),
+            game.keys.key(KeyAction::CursorRight)
+        ));
+
+        let timeout = match game.notifications.update(Instant::now()) {
+            Some(duration) => crossbeam_channel::after(duration),
+            None => crossbeam_channel::never(),
+        };
+
+        terminal.draw(|mut f| {
+            game.draw(&mut f);
+        })?;
+
+        let event = crossbeam_channel::select! {
+            recv(rx) -> event => Some(event?),
+            recv(timeout) -> _ => None,
+            recv(ticker) -> _ => None,
+        };
+
+        if let Some(Event::Key(key)) = event {
+            match (game.keys.action(key, false), key.code) {
+                (Some(KeyAction::Quit), _) => break,
+                (Some(KeyAction::Pause), _) => paused = !paused,
+                (Some(KeyAction::CursorRight), _) => {
+                    paused = true;
+                    if let Some(action) = replay.actions.get(next) {
+                        position = Duration::from_millis(action.time);
+                    } else {
+                        position = end;
+                    }
+                }
+                (_, KeyCode::Char('+')) => speed = (speed * 2.0).min(MAX_SPEED),
+                (_, KeyCode::Char('-')) => speed = (speed / 2.0).max(MIN_SPEED),
+                _ => (),
+            }
+        }
+    }
+
+    game.caption = if game.status() == State::Playing {
+        Some("End of replay".to_owned())
+    } else {
+        None
+    };
+    game.close(terminal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Guess;
+    use std::num::NonZeroU64;
+
+    #[test]
+    fn reproduces_game() {
+        let mut game = Game::with_seed(Settings::default(), 42);
+        game.current_guess = Guess(vec![0, 1, 2, 3]);
+        game.on_enter();
+        game.place(4);
+
+        let mut replay = Replay::new(&game);
+        replay.push(Duration::from_secs(1), Action::Undo);
+        replay.push(Duration::from_secs(2), Action::Place(5));
+        replay.finish(Duration::from_secs(3));
+        assert!(replay.validate().is_ok());
+
+        let mut replayed = replay.game();
+        for action in &replay.actions {
+            perform(&mut replayed, action.action);
+        }
+        assert_eq!(replayed.solution, game.solution);
+        assert_eq!(replayed.guesses, game.guesses);
+        assert_eq!(replayed.current_guess, Guess(vec![5]));
+        assert_eq!(replay.end, 3000);
+    }
+
+    #[test]
+    fn records_earlier_forfeited_turns() {
+        let settings = Settings {
+            turn_time: NonZeroU64::new(10),
+            forfeit_turn: true,
+            ..Default::default()
+        };
+        let mut game = Game::with_seed(settings, 0);
+        game.forfeit_turn();
+        game.current_guess = Guess(vec![0, 1, 2, 3]);
+        game.on_enter();
+
+        let replay = Replay::new(&game);
+        let mut replayed = replay.game();
+        for action in &replay.actions {
+            perform(&mut replayed, action.action);
+        }
+        assert_eq!(replayed.guesses, game.guesses);
+        assert_eq!(replayed.hints, game.hints);
+    }
+
+    #[test]
+    fn reproduces_giving_up() {
+        let game = Game::with_seed(Settings::default(), 0);
+        let mut replay = Replay::new(&game);
+        replay.push(Duration::from_secs(1), Action::GiveUp);
+
+        let mut replayed = replay.game();
+        for action in &replay.actions {
+            perform(&mut replayed, action.action);
+        }
+        assert!(replayed.status() == State::Lost);
+        assert_eq!(replayed.result_message(), "You gave up");
+    }
+
+    #[test]
+    fn reject_out_of_range_color() {
+        let game = Game::with_seed(Settings::default(), 0);
+        let mut replay = Replay::new(&game);
+        replay.push(Duration::from_secs(0), Action::Place(6));
+        assert!(replay.validate().is_err());
+    }
+}
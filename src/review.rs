@@ -0,0 +1,208 @@
+//! Rates the guesses of a finished game against the best guesses at the
+//! time
+
+use crate::solver::{self, Score, Suggestion};
+use crate::{Guess, Hint, Settings};
+use crossbeam_channel::{Receiver, Sender};
+use std::fmt;
+use std::thread;
+
+/// Bits of information a guess may lose against the best guess and still
+/// be rated as such, to absorb rounding errors
+const BEST_TOLERANCE: f64 = 1e-6;
+
+/// Bits lost against the best guess up to which a guess is good
+const GOOD_LOSS: f64 = 0.25;
+
+/// Bits lost against the best guess up to which a guess is an inaccuracy
+const INACCURACY_LOSS: f64 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rating {
+    Best,
+    Good,
+    Inaccuracy,
+    Blunder,
+}
+
+impl Rating {
+    /// Rates a guess by how much less information it gives than the best one
+    fn new(score: &Score, best: &Score) -> Self {
+        let loss = best.entropy - score.entropy;
+        if loss <= BEST_TOLERANCE {
+            Self::Best
+        } else if loss <= GOOD_LOSS {
+            Self::Good
+        } else if loss <= INACCURACY_LOSS {
+            Self::Inaccuracy
+        } else {
+            Self::Blunder
+        }
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            Self::Best => "best",
+            Self::Good => "good",
+            Self::Inaccuracy => "inaccuracy",
+            Self::Blunder => "blunder",
+        };
+        f.pad(label)
+    }
+}
+
+/// How a guess compares with the best guess at the time
+#[derive(Debug, Clone)]
+pub struct Review {
+    pub score: Score,
+    pub best: Suggestion,
+    pub rating: Rating,
+}
+
+/// Rates each guess in turn, or gives `None` for a forfeited turn, which
+/// leaves an empty row. `codes` are all the codes of the settings.
+pub fn review(
+    settings: &Settings,
+    codes: &[Guess],
+    guesses: &[Guess],
+    hints: &[Hint],
+) -> Vec<Option<Review>> {
+    let num_colors = settings.code_colors();
+    let mut candidates = codes.to_vec();
+
+    guesses
+        .iter()
+        .zip(hints)
+        .map(|(guess, hint)| {
+            if guess.0.is_empty() {
+                return None;
+            }
+
+            let score = solver::score(guess, &candidates, settings);
+            let best = solver::suggest(settings, codes, &candidates, 1)
+                .pop()
+                .unwrap_or_else(|| Suggestion {
+                    guess: guess.clone(),
+                    score,
+                    is_candidate: false,
+                });
+            let review = Review {
+                score,
+                rating: Rating::new(&score, &best.score),
+                best,
+            };
+
+            candidates.retain(|code| {
+                solver::is_consistent(
+                    code,
+                    std::slice::from_ref(guess),
+                    std::slice::from_ref(hint),
+                    num_colors,
+                )
+            });
+            Some(review)
+        })
+        .collect()
+}
+
+/// Reviews a game in a background thread so that the UI stays responsive
+pub struct Reviewer {
+    /// Keeps the channel open even if the analysis fails
+    _tx: Sender<Vec<Option<Review>>>,
+    rx: Receiver<Vec<Option<Review>>>,
+    reviews: Option<Vec<Option<Review>>>,
+    /// Whether there are few enough codes to analyze
+    available: bool,
+}
+
+impl Reviewer {
+    /// Starts reviewing the guesses
+    pub fn new(settings: &Settings, guesses: &[Guess], hints: &[Hint]) -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let available = solver::code_count(settings).is_some();
+        if available {
+            let tx = tx.clone();
+            let settings = settings.clone();
+            let guesses = guesses.to_vec();
+            let hints = hints.to_vec();
+            thread::spawn(move || {
+                let codes = solver::all_codes(&settings);
+                let _ = tx.send(review(&settings, &codes, &guesses, &hints));
+            });
+        }
+
+        Self {
+            _tx: tx,
+            rx,
+            reviews: None,
+            available,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.available
+    }
+
+    pub fn receiver(&self) -> &Receiver<Vec<Option<Review>>> {
+        &self.rx
+    }
+
+    pub fn on_reviews(&mut self, reviews: Vec<Option<Review>>) {
+        self.reviews = Some(reviews);
+    }
+
+    /// Reviews of the guesses in order, once the analysis is done
+    pub fn reviews(&self) -> Option<&[Option<Review>]> {
+        self.reviews.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_hint;
+
+    #[test]
+    fn rate_guesses() {
+        let settings = Settings::default();
+        let codes = solver::all_codes(&settings);
+        let solution = Guess(vec![1, 2, 4, 5]);
+        let guesses = vec![
+            // the most informative opening under the classic rules
+            Guess(vec![0, 1, 2, 3]),
+            // gives away almost nothing
+            Guess(vec![5, 5, 5, 5]),
+            solution.clone(),
+        ];
+        let hints: Vec<_> = guesses
+            .iter()
+            .map(|guess| calc_hint(guess, &solution, 6))
+            .collect();
+
+        let reviews: Vec<_> = review(&settings, &codes, &guesses, &hints)
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        assert_eq!(reviews.len(), 3);
+        assert_eq!(reviews[0].rating, Rating::Best);
+        assert_eq!(reviews[1].rating, Rating::Blunder);
+        assert!(reviews[1].best.score.entropy > reviews[1].score.entropy);
+    }
+
+    #[test]
+    fn skip_forfeited_turns() {
+        let settings = Settings::default();
+        let codes = solver::all_codes(&settings);
+        let solution = Guess(vec![1, 2, 4, 5]);
+        let opening = Guess(vec![0, 1, 2, 3]);
+        let guesses = vec![Guess::default(), opening.clone()];
+        let hints = vec![Hint::default(), calc_hint(&opening, &solution, 6)];
+
+        let reviews = review(&settings, &codes, &guesses, &hints);
+        assert!(reviews[0].is_none());
+        // the forfeited turn rules out no codes
+        assert_eq!(reviews[1].as_ref().unwrap().rating, Rating::Best);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::keymap::KeyMap;
+use crate::theme::ThemeConfig;
+use crate::Settings;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// Named set of house rules which can be shared as a file
+#[derive(Serialize, Deserialize)]
+pub struct Ruleset {
+    pub name: String,
+    pub settings: Settings,
+    /// Color theme, defined in full so that it does not depend on the
+    /// config of the author
+    #[serde(default)]
+    pub theme: Option<ThemeConfig>,
+    /// Keys of the actions as in the `[keys]` table of the config, which
+    /// take precedence over the player's own
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+}
+
+impl Ruleset {
+    /// Names the rule set after the file it is going to be stored in
+    pub fn new(
+        path: &Path,
+        settings: Settings,
+        theme: Option<ThemeConfig>,
+        keys: BTreeMap<String, String>,
+    ) -> Self {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self {
+            name,
+            settings,
+            theme,
+            keys,
+        }
+    }
+}
+
+pub fn store(path: &Path, ruleset: &Ruleset) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), ruleset)?;
+
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<Ruleset> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let ruleset: Ruleset = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    ruleset
+        .settings
+        .validate()
+        .and_then(|()| KeyMap::parse(&ruleset.keys).map(|_| ()))
+        .and_then(|()| {
+            ruleset
+                .theme
+                .as_ref()
+                .map_or(Ok(()), |theme| theme.build().map(|_| ()))
+        })
+        .with_context(|| format!("invalid rule set {}", path.display()))?;
+
+    Ok(ruleset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("codebreaker-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_path("house-rules");
+        let settings = Settings {
+            no_duplicate: true,
+            hard: true,
+            ..Default::default()
+        };
+        let theme: ThemeConfig = toml::from_str(
+            r#"
+            base = "light"
+            bull = "magenta"
+            "#,
+        )
+        .unwrap();
+        let mut keys = BTreeMap::new();
+        keys.insert("give-up".to_owned(), "ctrl+g".to_owned());
+        let ruleset = Ruleset::new(&path, settings.clone(), Some(theme.clone()), keys);
+        store(&path, &ruleset).unwrap();
+
+        let loaded = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            loaded.name,
+            format!("codebreaker-house-rules-{}", std::process::id())
+        );
+        assert_eq!(loaded.settings, settings);
+        assert_eq!(loaded.theme, Some(theme));
+        assert_eq!(loaded.keys, ruleset.keys);
+    }
+
+    #[test]
+    fn reject_invalid_keys() {
+        let path = temp_path("invalid-keys");
+        let mut keys = BTreeMap::new();
+        keys.insert("fly".to_owned(), "f".to_owned());
+        store(&path, &Ruleset::new(&path, Settings::default(), None, keys)).unwrap();
+
+        let loaded = load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn reject_invalid_theme() {
+        let path = temp_path("invalid-theme");
+        let theme: ThemeConfig = toml::from_str(r#"bull = "mauve""#).unwrap();
+        let ruleset = Ruleset::new(&path, Settings::default(), Some(theme), BTreeMap::new());
+        store(&path, &ruleset).unwrap();
+
+        let loaded = load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::stopwatch::Stopwatch;
+use crate::{calc_hint, generate_solution, Game, Guess, Hint, Settings};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::Duration;
+
+/// Snapshot of a game in progress
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    settings: Settings,
+    seed: u64,
+    solution: Guess,
+    guesses: Vec<Guess>,
+    hints: Vec<Hint>,
+    current_guess: Guess,
+    #[serde(default)]
+    cursor: Option<usize>,
+    #[serde(default)]
+    insert_mode: bool,
+    #[serde(default)]
+    gave_up: bool,
+    #[serde(default)]
+    daily: Option<u64>,
+    /// Time spent playing in seconds
+    #[serde(default)]
+    elapsed: f64,
+}
+
+pub fn store(path: &Path, game: &Game) -> Result<()> {
+    let saved = SavedGame {
+        settings: game.settings.clone(),
+        seed: game.seed,
+        solution: game.solution.clone(),
+        guesses: game.guesses.clone(),
+        hints: game.hints.clone(),
+        current_guess: game.current_guess.clone(),
+        cursor: game.cursor,
+        insert_mode: game.insert_mode,
+        gave_up: game.gave_up,
+        daily: game.daily,
+        elapsed: game.stopwatch.elapsed().as_secs_f64(),
+    };
+
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &saved)?;
+
+    Ok(())
+}
+
+/// Removes the saved game once it is over, as it cannot be resumed
+pub fn remove(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<Game> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let saved: SavedGame = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    saved
+        .validate()
+        .with_context(|| format!("invalid saved game {}", path.display()))?;
+
+    let mut game = Game::new(saved.settings);
+    game.seed = saved.seed;
+    game.solution = saved.solution;
+    game.guesses = saved.guesses;
+    game.hints = saved.hints;
+    game.current_guess = saved.current_guess;
+    game.cursor = saved.cursor;
+    game.insert_mode = saved.insert_mode;
+    game.gave_up = saved.gave_up;
+    game.daily = saved.daily;
+    game.stopwatch = Stopwatch::with_elapsed(
+        Duration::try_from_secs_f64(saved.elapsed)
+            .with_context(|| format!("invalid saved game {}", path.display()))?,
+    );
+
+    Ok(game)
+}
+
+impl SavedGame {
+    fn validate(&self) -> Result<()> {
+        let settings = &self.settings;
+        settings.validate()?;
+
+        if generate_solution(settings, self.seed) != self.solution {
+            return Err(anyhow::anyhow!("solution does not match the seed"));
+        }
+        if self.guesses.len() != self.hints.len() {
+            return Err(anyhow::anyhow!("number of guesses and hints differ"));
+        }
+        if self.guesses.len() > settings.guesses.get() {
+            return Err(anyhow::anyhow!("too many guesses"));
+        }
+
+        for guess in self.guesses.iter().chain(std::iter::once(&self.solution)) {
+            // a forfeited turn leaves an empty row
+            let forfeited = settings.forfeit_turn && guess.0.is_empty();
+            if guess.0.len() != settings.holes.get() && !forfeited {
+                return Err(anyhow::anyhow!("wrong number of holes in a row"));
+            }
+        }
+        for guess in self
+            .guesses
+            .iter()
+            .chain(std::iter::once(&self.solution))
+            .chain(std::iter::once(&self.current_guess))
+        {
+            if guess.0.iter().any(|c| *c >= settings.code_colors()) {
+                return Err(anyhow::anyhow!("color out of range"));
+            }
+        }
+        if self.current_guess.0.len() > settings.holes.get() {
+            return Err(anyhow::anyhow!("too many pegs in the current row"));
+        }
+        if self
+            .cursor
+            .is_some_and(|cursor| cursor >= self.current_guess.0.len())
+        {
+            return Err(anyhow::anyhow!("cursor out of the current row"));
+        }
+        if !self.elapsed.is_finite() || self.elapsed < 0.0 {
+            return Err(anyhow::anyhow!("invalid elapsed time"));
+        }
+
+        for (guess, hint) in self.guesses.iter().zip(self.hints.iter()) {
+            if calc_hint(guess, &self.solution, settings.code_colors()) != *hint {
+                return Err(anyhow::anyhow!("hints do not match the solution"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn settings() -> Settings {
+        Settings::default()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "codebreaker-save-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_path("round-trip");
+
+        let mut game = Game::new(settings());
+        game.current_guess = Guess(vec![0, 1, 2, 3]);
+        game.on_enter();
+        game.current_guess = Guess(vec![4, 5]);
+        game.cursor = Some(1);
+        game.insert_mode = true;
+        store(&path, &game).unwrap();
+
+        let loaded = load(&path).unwrap();
+        remove(&path).unwrap();
+        assert!(!path.exists());
+
+        assert_eq!(loaded.seed, game.seed);
+        assert_eq!(loaded.solution, game.solution);
+        assert_eq!(loaded.guesses, game.guesses);
+        assert_eq!(loaded.hints, game.hints);
+        assert_eq!(loaded.current_guess, game.current_guess);
+        assert_eq!(loaded.cursor, Some(1));
+        assert!(loaded.insert_mode);
+    }
+
+    #[test]
+    fn keep_giving_up() {
+        let path = temp_path("gave-up");
+
+        let mut game = Game::new(settings());
+        game.gave_up = true;
+        store(&path, &game).unwrap();
+
+        let loaded = load(&path).unwrap();
+        remove(&path).unwrap();
+        assert!(loaded.gave_up);
+    }
+
+    #[test]
+    fn reject_huge_elapsed_time() {
+        let path = temp_path("huge-elapsed");
+
+        let mut game = Game::new(settings());
+        game.stopwatch = Stopwatch::with_elapsed(Duration::from_secs(1));
+        store(&path, &game).unwrap();
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, json.replace("\"elapsed\": 1.0", "\"elapsed\": 1e30")).unwrap();
+
+        let loaded = load(&path);
+        remove(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn reject_tampered_hints() {
+        let seed = 42;
+        let solution = generate_solution(&settings(), seed);
+        let guess = Guess(vec![0, 0, 0, 0]);
+        let mut hint = calc_hint(&guess, &solution, 6);
+        hint.bulls = (hint.bulls + 1) % 5;
+
+        let saved = SavedGame {
+            settings: settings(),
+            seed,
+            solution,
+            guesses: vec![guess],
+            hints: vec![hint],
+            current_guess: Guess::default(),
+            cursor: None,
+            insert_mode: false,
+            gave_up: false,
+            daily: None,
+            elapsed: 0.0,
+        };
+        assert!(saved.validate().is_err());
+    }
+}
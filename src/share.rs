@@ -0,0 +1,173 @@
+//! Spoiler-free card of a finished game, like the ones of Wordle, showing
+//! only the hints of the guesses
+
+use crate::glyphs::Glyphs;
+use crate::stopwatch::format_duration;
+use crate::{daily, Game, Hint, Settings, State};
+use anyhow::{Context, Result};
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Key pegs of the card: correct position, wrong position, and neither
+const EMOJI: [&str; 3] = ["🟩", "🟨", "⬛"];
+const ASCII: [&str; 3] = ["X", "o", "."];
+
+/// Commands which put their stdin on the clipboard, tried in order
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip.exe"],
+];
+
+/// Text of the card, e.g.
+///
+/// ```text
+/// codebreaker Daily #12 5/8
+/// 6 colors, 4 holes
+///
+/// 🟩🟨⬛⬛
+/// ...
+/// ```
+pub fn card(game: &Game, emoji: bool) -> String {
+    let max_guesses = game.settings.guesses.get();
+    let result = match game.status() {
+        State::Won => format!("{}/{}", game.guesses.len(), max_guesses),
+        _ => format!("X/{}", max_guesses),
+    };
+    let title = match game.daily {
+        Some(day) => format!(
+            "{} Daily #{} {}",
+            env!("CARGO_PKG_NAME"),
+            daily::puzzle_number(day),
+            result
+        ),
+        None => format!("{} {}", env!("CARGO_PKG_NAME"), result),
+    };
+
+    let pegs = if emoji { &EMOJI } else { &ASCII };
+    let rows = game
+        .hints
+        .iter()
+        .map(|hint| hint_row(hint, &game.settings, pegs));
+
+    let mut lines = vec![title, rules(&game.settings), String::new()];
+    lines.extend(rows);
+    lines.join("\n")
+}
+
+/// e.g. "6 colors, 4 holes, no duplicate"
+fn rules(settings: &Settings) -> String {
+    let mut rules = vec![if settings.digits {
+        format!("{} digits", settings.holes)
+    } else {
+        format!("{} colors, {} holes", settings.colors, settings.holes)
+    }];
+    if settings.no_duplicate && !settings.digits {
+        rules.push("no duplicate".to_owned());
+    }
+    let variants = [
+        (settings.allow_empty, "blanks"),
+        (settings.hard, "hard"),
+        (settings.blind, "blind"),
+        (settings.static_hints, "static"),
+    ];
+    rules.extend(
+        variants
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, name)| name.to_string()),
+    );
+    if let Some(limit) = settings.time_limit {
+        rules.push(format!(
+            "{} limit",
+            format_duration(Duration::from_secs(limit.get()))
+        ));
+    }
+    if let Some(turn_time) = settings.turn_time {
+        rules.push(format!(
+            "{} per guess",
+            format_duration(Duration::from_secs(turn_time.get()))
+        ));
+    }
+    rules.join(", ")
+}
+
+fn hint_row(hint: &Hint, settings: &Settings, pegs: &[&str; 3]) -> String {
+    let (bulls, cows) = if settings.blind {
+        (0, hint.bulls + hint.cows)
+    } else {
+        (hint.bulls, hint.cows)
+    };
+    let misses = settings.holes.get() - bulls - cows;
+    pegs[0].repeat(bulls) + &pegs[1].repeat(cows) + &pegs[2].repeat(misses)
+}
+
+/// Whether the terminal is likely to show the emoji, which it is not when
+/// the board is drawn in ASCII or the terminal is monochrome or dumb
+pub fn supports_emoji(glyphs: &Glyphs) -> bool {
+    !glyphs.ascii
+        && env::var_os("NO_COLOR").is_none()
+        && env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+/// Puts the text on the clipboard with the first clipboard command found
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    for command in CLIPBOARD_COMMANDS {
+        let child = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            // not installed
+            Err(_) => continue,
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child
+            .wait()
+            .with_context(|| format!("failed to run {}", command[0]))?;
+        if status.success() {
+            return Ok(());
+        }
+    }
+    Err(anyhow::anyhow!("no clipboard command is available"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Guess;
+
+    #[test]
+    fn hide_the_guesses() {
+        let mut game = Game::with_seed(Settings::default(), 0);
+        let solution = game.solution.clone();
+        let wrong = Guess(solution.0.iter().rev().copied().collect());
+        for guess in &[wrong, solution] {
+            game.current_guess = guess.clone();
+            game.on_enter();
+        }
+
+        let card = card(&game, false);
+        let lines: Vec<_> = card.lines().collect();
+        assert_eq!(lines[0], "codebreaker 2/8");
+        assert_eq!(lines[1], "6 colors, 4 holes");
+        assert_eq!(lines[3].len(), 4);
+        assert_eq!(lines[4], "XXXX");
+
+        game.daily = Some(0);
+        game.settings.no_duplicate = true;
+        let card = super::card(&game, true);
+        assert!(card.starts_with("codebreaker Daily #1 2/8\n6 colors, 4 holes, no duplicate"));
+        assert!(card.ends_with("🟩🟩🟩🟩"));
+    }
+}
@@ -0,0 +1,433 @@
+use crate::{calc_hint, Guess, Hint, Settings};
+use rand::prelude::*;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::sync::atomic::{self, AtomicUsize};
+use std::thread;
+
+/// Upper bound of the number of codes the solver enumerates
+pub const MAX_CODES: usize = 1_000_000;
+
+/// Upper bound of the number of hints calculated to rank guesses
+const MAX_EVALUATIONS: usize = 4_000_000;
+
+/// Number of hints calculated below which spawning threads does not pay off
+const MIN_PARALLEL_EVALUATIONS: usize = 50_000;
+
+/// Maximum number of threads to rank guesses on, or 0 for as many as the
+/// CPUs
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Caps the number of threads to rank guesses on. 0 uses all the CPUs.
+pub fn set_threads(threads: usize) {
+    THREADS.store(threads, atomic::Ordering::Relaxed);
+}
+
+fn num_threads() -> usize {
+    match THREADS.load(atomic::Ordering::Relaxed) {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+}
+
+/// Number of possible codes, or `None` if it exceeds `MAX_CODES`
+pub fn code_count(settings: &Settings) -> Option<usize> {
+    let colors = settings.code_colors();
+    let holes = settings.holes.get();
+
+    let mut count = 1usize;
+    for i in 0..holes {
+        let choices = if settings.no_duplicate {
+            colors.checked_sub(i)?
+        } else {
+            colors
+        };
+        count = count.checked_mul(choices)?;
+        if count > MAX_CODES {
+            return None;
+        }
+    }
+
+    Some(count)
+}
+
+/// All the codes in lexicographic order. Callers are expected to check
+/// `code_count` beforehand.
+pub fn all_codes(settings: &Settings) -> Vec<Guess> {
+    let colors = settings.code_colors();
+    let holes = settings.holes.get();
+
+    let mut codes = Vec::new();
+    let mut code = vec![0; holes];
+    loop {
+        if !settings.no_duplicate || !has_duplicate(&code) {
+            codes.push(Guess(code.clone()));
+        }
+
+        // increment like an odometer
+        let mut i = holes;
+        loop {
+            if i == 0 {
+                return codes;
+            }
+            i -= 1;
+            code[i] += 1;
+            if code[i] < colors {
+                break;
+            }
+            code[i] = 0;
+        }
+    }
+}
+
+fn has_duplicate(code: &[usize]) -> bool {
+    code.iter()
+        .enumerate()
+        .any(|(i, x)| code[i + 1..].contains(x))
+}
+
+/// Whether `code` could be the solution given the guesses made so far
+pub fn is_consistent(code: &Guess, guesses: &[Guess], hints: &[Hint], num_colors: usize) -> bool {
+    guesses
+        .iter()
+        .zip(hints.iter())
+        .all(|(guess, hint)| calc_hint(guess, code, num_colors) == *hint)
+}
+
+/// Codes which could still be the solution
+pub fn candidates(
+    codes: &[Guess],
+    guesses: &[Guess],
+    hints: &[Hint],
+    num_colors: usize,
+) -> Vec<Guess> {
+    codes
+        .iter()
+        .filter(|code| is_consistent(code, guesses, hints, num_colors))
+        .cloned()
+        .collect()
+}
+
+/// Most frequent color of each hole among the candidates, together with
+/// its share of the candidates
+pub fn likely_colors(candidates: &[Guess], settings: &Settings) -> Vec<(usize, f64)> {
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+
+    (0..settings.holes.get())
+        .map(|hole| {
+            let mut counts = vec![0usize; settings.code_colors()];
+            for candidate in candidates {
+                counts[candidate.0[hole]] += 1;
+            }
+
+            let (color, count) = counts
+                .into_iter()
+                .enumerate()
+                .max_by_key(|(_, count)| *count)
+                .unwrap();
+            (color, count as f64 / candidates.len() as f64)
+        })
+        .collect()
+}
+
+/// How well a guess splits the candidates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    /// Expected information gained from the hint, in bits
+    pub entropy: f64,
+    /// Expected number of candidates left after the hint
+    pub expected_remaining: f64,
+    /// Number of candidates left after the least informative hint
+    pub worst_case: usize,
+}
+
+/// Number of candidates giving each possible hint
+fn partition(guess: &Guess, candidates: &[Guess], settings: &Settings) -> Vec<usize> {
+    let holes = settings.holes.get();
+    let mut counts = vec![0usize; (holes + 1) * (holes + 1)];
+    for candidate in candidates {
+        let hint = calc_hint(guess, candidate, settings.code_colors());
+        counts[hint.bulls * (holes + 1) + hint.cows] += 1;
+    }
+    counts
+}
+
+pub fn score(guess: &Guess, candidates: &[Guess], settings: &Settings) -> Score {
+    let total = candidates.len() as f64;
+    let counts = partition(guess, candidates, settings);
+
+    let mut entropy = 0.0;
+    let mut expected_remaining = 0.0;
+    for count in counts.iter().filter(|count| **count > 0) {
+        let p = *count as f64 / total;
+        entropy -= p * p.log2();
+        expected_remaining += p * *count as f64;
+    }
+
+    Score {
+        entropy,
+        expected_remaining,
+        worst_case: counts.into_iter().max().unwrap_or(0),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub guess: Guess,
+    pub score: Score,
+    /// Whether the guess could be the solution itself
+    pub is_candidate: bool,
+}
+
+/// Guesses ranked by how much information they are expected to give,
+/// best first
+pub fn suggest(
+    settings: &Settings,
+    codes: &[Guess],
+    candidates: &[Guess],
+    n: usize,
+) -> Vec<Suggestion> {
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+
+    let mut suggestions: Vec<_> = par_chunks(&pool(codes, candidates), candidates, |guesses| {
+        let mut suggestions: Vec<_> = guesses
+            .iter()
+            .map(|guess| Suggestion {
+                guess: guess.clone(),
+                score: score(guess, candidates, settings),
+                is_candidate: candidates.binary_search(guess).is_ok(),
+            })
+            .collect();
+        suggestions.sort_by(compare_suggestions);
+        suggestions.truncate(n);
+        suggestions
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    suggestions.sort_by(compare_suggestions);
+    suggestions.truncate(n);
+    suggestions
+}
+
+/// Guess minimizing the number of candidates left after the least
+/// informative hint, preferring candidates and then the smallest code as
+/// in Knuth's algorithm
+pub fn minimax(settings: &Settings, codes: &[Guess], candidates: &[Guess]) -> Option<Guess> {
+    if candidates.is_empty() {
+        return None;
+    }
+
+    par_chunks(&pool(codes, candidates), candidates, |guesses| {
+        let mut best: Option<(usize, bool, &Guess)> = None;
+        for guess in guesses {
+            let bound = best.map_or(usize::MAX, |(worst_case, _, _)| worst_case);
+            let worst_case = match worst_case_within(guess, candidates, settings, bound) {
+                Some(worst_case) => worst_case,
+                None => continue,
+            };
+            let is_candidate = candidates.binary_search(guess).is_ok();
+            let entry = (worst_case, !is_candidate, guess);
+            if best.is_none_or(|best| entry < best) {
+                best = Some(entry);
+            }
+        }
+        best
+    })
+    .into_iter()
+    .flatten()
+    .min()
+    .map(|(_, _, guess)| guess.clone())
+}
+
+/// Number of candidates left after the least informative hint, or `None`
+/// as soon as it turns out to exceed `bound`
+fn worst_case_within(
+    guess: &Guess,
+    candidates: &[Guess],
+    settings: &Settings,
+    bound: usize,
+) -> Option<usize> {
+    let holes = settings.holes.get();
+    let mut counts = vec![0usize; (holes + 1) * (holes + 1)];
+    let mut worst_case = 0;
+    for candidate in candidates {
+        let hint = calc_hint(guess, candidate, settings.code_colors());
+        let count = &mut counts[hint.bulls * (holes + 1) + hint.cows];
+        *count += 1;
+        if *count > bound {
+            return None;
+        }
+        worst_case = worst_case.max(*count);
+    }
+    Some(worst_case)
+}
+
+/// Splits the guesses among threads and collects the results of `f` for
+/// each share, in order
+fn par_chunks<'a, T, F>(guesses: &'a [Guess], candidates: &[Guess], f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&'a [Guess]) -> T + Sync,
+{
+    let evaluations = guesses.len().saturating_mul(candidates.len());
+    let threads = if evaluations < MIN_PARALLEL_EVALUATIONS {
+        1
+    } else {
+        num_threads().clamp(1, guesses.len().max(1))
+    };
+    if threads == 1 {
+        return vec![f(guesses)];
+    }
+
+    let chunk_size = guesses.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let f = &f;
+        let handles: Vec<_> = guesses
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || f(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Guesses worth evaluating. Guessing non-candidates can be more
+/// informative, but evaluating all the codes is too expensive for large
+/// spaces, where a sample of the candidates is taken instead. The seed is
+/// fixed so that the same board gets the same suggestions.
+fn pool<'a>(codes: &'a [Guess], candidates: &'a [Guess]) -> Cow<'a, [Guess]> {
+    if codes.len().saturating_mul(candidates.len()) <= MAX_EVALUATIONS {
+        Cow::Borrowed(codes)
+    } else {
+        let len = (MAX_EVALUATIONS / candidates.len()).clamp(1, candidates.len());
+        let mut rng = StdRng::seed_from_u64(0);
+        Cow::Owned(candidates.choose_multiple(&mut rng, len).cloned().collect())
+    }
+}
+
+fn compare_suggestions(a: &Suggestion, b: &Suggestion) -> Ordering {
+    b.score
+        .entropy
+        .partial_cmp(&a.score.entropy)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| b.is_candidate.cmp(&a.is_candidate))
+        .then_with(|| a.guess.0.cmp(&b.guess.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    fn settings(colors: usize, holes: usize, no_duplicate: bool) -> Settings {
+        Settings {
+            colors: NonZeroUsize::new(colors).unwrap(),
+            holes: NonZeroUsize::new(holes).unwrap(),
+            no_duplicate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn count_codes() {
+        assert_eq!(code_count(&settings(6, 4, false)), Some(1296));
+        assert_eq!(code_count(&settings(6, 4, true)), Some(360));
+        assert_eq!(code_count(&settings(7, 20, false)), None);
+
+        assert_eq!(all_codes(&settings(6, 4, false)).len(), 1296);
+        assert_eq!(all_codes(&settings(6, 4, true)).len(), 360);
+    }
+
+    #[test]
+    fn sample_large_pool() {
+        let codes = all_codes(&settings(6, 6, false));
+        let sample = pool(&codes, &codes);
+        assert_eq!(sample.len(), MAX_EVALUATIONS / codes.len());
+        // not just the codes starting with the first colors
+        assert!(sample.iter().any(|code| code.0[0] != 0));
+        assert_eq!(sample, pool(&codes, &codes));
+    }
+
+    #[test]
+    fn filter_candidates() {
+        let settings = settings(6, 4, false);
+        let codes = all_codes(&settings);
+        let solution = Guess(vec![0, 1, 2, 3]);
+        let guesses = vec![Guess(vec![0, 0, 1, 1]), Guess(vec![2, 3, 4, 5])];
+        let hints: Vec<_> = guesses
+            .iter()
+            .map(|guess| calc_hint(guess, &solution, 6))
+            .collect();
+
+        let candidates = candidates(&codes, &guesses, &hints, 6);
+        assert!(candidates.contains(&solution));
+        assert!(candidates
+            .iter()
+            .all(|code| is_consistent(code, &guesses, &hints, 6)));
+    }
+
+    #[test]
+    fn likely_colors_of_holes() {
+        let settings = settings(3, 2, false);
+        let candidates = vec![
+            Guess(vec![0, 1]),
+            Guess(vec![0, 2]),
+            Guess(vec![1, 2]),
+            Guess(vec![0, 2]),
+        ];
+        assert_eq!(
+            likely_colors(&candidates, &settings),
+            vec![(0, 0.75), (2, 0.75)]
+        );
+    }
+
+    #[test]
+    fn rank_by_entropy() {
+        let settings = settings(6, 4, false);
+        let codes = all_codes(&settings);
+        let suggestions = suggest(&settings, &codes, &codes, 3);
+
+        assert_eq!(suggestions.len(), 3);
+        assert!(suggestions[0].score.entropy >= suggestions[1].score.entropy);
+        assert!(suggestions[1].score.entropy >= suggestions[2].score.entropy);
+        // a guess with a single color gives little information
+        assert!(
+            suggestions[0].score.entropy > score(&Guess(vec![0; 4]), &codes, &settings).entropy
+        );
+    }
+
+    #[test]
+    fn minimax_opening() {
+        let settings = settings(6, 4, false);
+        let codes = all_codes(&settings);
+
+        // Knuth's first guess 1122 leaves at most 256 candidates
+        let guess = minimax(&settings, &codes, &codes).unwrap();
+        assert_eq!(guess, Guess(vec![0, 0, 1, 1]));
+        assert_eq!(score(&guess, &codes, &settings).worst_case, 256);
+    }
+
+    #[test]
+    fn prune_worse_guesses() {
+        let settings = settings(6, 4, false);
+        let codes = all_codes(&settings);
+        let guess = Guess(vec![0, 0, 0, 0]);
+
+        let worst_case = score(&guess, &codes, &settings).worst_case;
+        assert_eq!(
+            worst_case_within(&guess, &codes, &settings, worst_case),
+            Some(worst_case)
+        );
+        assert_eq!(
+            worst_case_within(&guess, &codes, &settings, worst_case - 1),
+            None
+        );
+    }
+}
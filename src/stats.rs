@@ -0,0 +1,396 @@
+use crate::{data_path, Settings, MAX_COLORS};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+static STATS_FILE_NAME: &str = "stats.json";
+
+/// Maximum length of bars in the guess distribution
+const HISTOGRAM_WIDTH: usize = 20;
+
+/// Number of games needed before suggesting another configuration
+const MIN_GAMES_FOR_SUGGESTION: usize = 10;
+
+/// Win rates above and below which another configuration is suggested
+const HIGH_WIN_RATE: f64 = 0.9;
+const LOW_WIN_RATE: f64 = 0.5;
+
+/// Number of spare guesses on average above which the configuration is
+/// considered too easy
+const COMFORTABLE_MARGIN: f64 = 2.0;
+
+/// Statistics of games played with a configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Record {
+    pub colors: usize,
+    pub holes: usize,
+    pub no_duplicate: bool,
+    #[serde(default)]
+    pub allow_empty: bool,
+    #[serde(default)]
+    pub blind: bool,
+    #[serde(default)]
+    pub static_hints: bool,
+    #[serde(default)]
+    pub hard: bool,
+    pub played: usize,
+    pub won: usize,
+    /// `distribution[i]` is the number of games won with `i + 1` guesses
+    pub distribution: Vec<usize>,
+    /// Most codes broken in a row with --marathon
+    #[serde(default)]
+    pub best_streak: usize,
+}
+
+impl Record {
+    fn matches(&self, settings: &Settings) -> bool {
+        self.colors == settings.colors.get()
+            && self.holes == settings.holes.get()
+            && self.no_duplicate == settings.no_duplicate
+            && self.allow_empty == settings.allow_empty
+            && self.blind == settings.blind
+            && self.static_hints == settings.static_hints
+            && self.hard == settings.hard
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.played == 0 {
+            0.0
+        } else {
+            self.won as f64 / self.played as f64
+        }
+    }
+
+    /// Average number of guesses of won games
+    pub fn average_guesses(&self) -> Option<f64> {
+        if self.won == 0 {
+            return None;
+        }
+
+        let total: usize = self
+            .distribution
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i + 1) * count)
+            .sum();
+        Some(total as f64 / self.won as f64)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Played {}  Won {:.0}%",
+            self.played,
+            self.win_rate() * 100.0
+        );
+        if let Some(average) = self.average_guesses() {
+            summary += &format!("  Average {:.1} guesses", average);
+        }
+        if self.best_streak > 0 {
+            summary += &format!("  Best streak {}", self.best_streak);
+        }
+        summary
+    }
+
+    /// Bar chart of the guess distribution, one line per number of guesses,
+    /// with bars drawn as repeated `bar`
+    pub fn histogram(&self, rows: usize, bar: &str) -> Vec<String> {
+        let rows = rows.max(self.distribution.len());
+        let max = self.distribution.iter().max().copied().unwrap_or(0).max(1);
+        let label_width = rows.to_string().len();
+
+        (0..rows)
+            .map(|i| {
+                let count = self.distribution.get(i).copied().unwrap_or(0);
+                let mut bar = bar.repeat((count * HISTOGRAM_WIDTH).div_ceil(max));
+                if !bar.is_empty() {
+                    bar.push(' ');
+                }
+                format!("{:>width$} {}{}", i + 1, bar, count, width = label_width)
+            })
+            .collect()
+    }
+
+    /// Harder or easier configuration to try, when the results so far are
+    /// too good or too bad for the rules `settings`
+    pub fn suggestion(&self, settings: &Settings) -> Option<String> {
+        if self.played < MIN_GAMES_FOR_SUGGESTION {
+            return None;
+        }
+
+        let colors = settings.colors.get();
+        let holes = settings.holes.get();
+        let win_rate = self.win_rate();
+        // games never won leave no margin to speak of
+        let comfortable = self
+            .average_guesses()
+            .is_some_and(|average| settings.guesses.get() as f64 - average >= COMFORTABLE_MARGIN);
+
+        let mut options = Vec::new();
+        if win_rate <= LOW_WIN_RATE {
+            // the variants making the game harder go first
+            let variants = [
+                (settings.time_limit.is_some(), "no time limit"),
+                (settings.turn_time.is_some(), "no turn time"),
+                (settings.hard, "no hard mode"),
+                (settings.blind, "no blind hints"),
+                (settings.static_hints, "no static hints"),
+                (settings.allow_empty, "no empty holes"),
+            ];
+            for (_, option) in variants.iter().filter(|(enabled, _)| *enabled) {
+                options.push((*option).to_owned());
+            }
+        }
+
+        if settings.digits {
+            // the digits are fixed, so only the holes can change
+            if win_rate >= HIGH_WIN_RATE && comfortable && holes < colors {
+                options.push(format!("{} holes", holes + 1));
+            } else if win_rate <= LOW_WIN_RATE && holes > 1 {
+                options.push(format!("{} holes", holes - 1));
+            }
+        } else if win_rate >= HIGH_WIN_RATE && comfortable {
+            if colors < MAX_COLORS {
+                options.push(format!("{} colors", colors + 1));
+            }
+            if settings.no_duplicate {
+                options.push("duplicates".to_owned());
+            }
+            if options.is_empty() {
+                options.push(format!("{} holes", holes + 1));
+            }
+        } else if win_rate <= LOW_WIN_RATE {
+            if colors > 2 && (!settings.no_duplicate || colors > holes) {
+                options.push(format!("{} colors", colors - 1));
+            } else if holes > 1 {
+                // every color is needed to fill the holes without duplicates
+                options.push(format!("{} holes", holes - 1));
+            }
+            if !settings.no_duplicate {
+                options.push("no duplicates".to_owned());
+            }
+        }
+
+        let (last, rest) = options.split_last()?;
+        let options = if rest.is_empty() {
+            last.clone()
+        } else {
+            format!("{} or {}", rest.join(", "), last)
+        };
+        Some(format!(
+            "You win {:.0}% of these games; try {}",
+            win_rate * 100.0,
+            options
+        ))
+    }
+
+    /// e.g. "6 colors, 4 holes, no duplicate, hard"
+    pub fn configuration(&self) -> String {
+        let mut configuration = format!("{} colors, {} holes", self.colors, self.holes);
+        let variants = [
+            (self.no_duplicate, "no duplicate"),
+            (self.allow_empty, "allow empty"),
+            (self.blind, "blind"),
+            (self.static_hints, "static"),
+            (self.hard, "hard"),
+        ];
+        for (_, name) in variants.iter().filter(|(enabled, _)| *enabled) {
+            configuration += ", ";
+            configuration += name;
+        }
+        configuration
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Stats {
+    records: Vec<Record>,
+}
+
+impl Stats {
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    pub fn get(&self, settings: &Settings) -> Option<&Record> {
+        self.records.iter().find(|r| r.matches(settings))
+    }
+
+    fn record_mut(&mut self, settings: &Settings) -> &mut Record {
+        if let Some(i) = self.records.iter().position(|r| r.matches(settings)) {
+            return &mut self.records[i];
+        }
+
+        self.records.push(Record {
+            colors: settings.colors.get(),
+            holes: settings.holes.get(),
+            no_duplicate: settings.no_duplicate,
+            allow_empty: settings.allow_empty,
+            blind: settings.blind,
+            static_hints: settings.static_hints,
+            hard: settings.hard,
+            ..Default::default()
+        });
+        self.records.last_mut().unwrap()
+    }
+
+    /// `guesses` is `None` when the game was lost
+    pub fn add(&mut self, settings: &Settings, guesses: Option<usize>) -> &Record {
+        let record = self.record_mut(settings);
+        record.played += 1;
+        if let Some(guesses) = guesses {
+            record.won += 1;
+            if record.distribution.len() < guesses {
+                record.distribution.resize(guesses, 0);
+            }
+            record.distribution[guesses - 1] += 1;
+        }
+        record
+    }
+
+    /// Keeps the streak of a marathon if it is the longest
+    pub fn add_streak(&mut self, settings: &Settings, streak: usize) {
+        let record = self.record_mut(settings);
+        record.best_streak = record.best_streak.max(streak);
+    }
+}
+
+pub fn load() -> Result<Stats> {
+    load_from(&data_path(STATS_FILE_NAME)?)
+}
+
+fn load_from(path: &Path) -> Result<Stats> {
+    if !path.exists() {
+        return Ok(Stats::default());
+    }
+
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let stats = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    Ok(stats)
+}
+
+pub fn store(stats: &Stats) -> Result<()> {
+    let path = data_path(STATS_FILE_NAME)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let file =
+        File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), stats)?;
+
+    Ok(())
+}
+
+/// Records the result of a game and returns the updated statistics of its
+/// configuration
+pub fn update(settings: &Settings, guesses: Option<usize>) -> Result<Record> {
+    let mut stats = load()?;
+    let record = stats.add(settings, guesses).clone();
+    store(&stats)?;
+
+    Ok(record)
+}
+
+/// Records the streak of a marathon so far
+pub fn update_streak(settings: &Settings, streak: usize) -> Result<()> {
+    let mut stats = load()?;
+    stats.add_streak(settings, streak);
+    store(&stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::{NonZeroU64, NonZeroUsize};
+
+    fn settings(colors: usize, no_duplicate: bool) -> Settings {
+        Settings {
+            colors: NonZeroUsize::new(colors).unwrap(),
+            no_duplicate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keyed_by_configuration() {
+        let mut stats = Stats::default();
+        stats.add(&settings(6, false), Some(3));
+        stats.add(&settings(6, false), Some(5));
+        stats.add(&settings(6, false), None);
+        stats.add(&settings(6, true), Some(4));
+        stats.add(&settings(7, false), None);
+        let mut hard = settings(6, false);
+        hard.hard = true;
+        stats.add(&hard, Some(6));
+
+        assert_eq!(stats.records().len(), 4);
+        assert_eq!(
+            stats.records()[3].configuration(),
+            "6 colors, 4 holes, hard"
+        );
+
+        let record = &stats.records()[0];
+        assert_eq!(record.played, 3);
+        assert_eq!(record.won, 2);
+        assert_eq!(record.distribution, vec![0, 0, 1, 0, 1]);
+        assert_eq!(record.average_guesses(), Some(4.0));
+
+        assert_eq!(stats.records()[2].average_guesses(), None);
+
+        stats.add_streak(&settings(6, true), 3);
+        stats.add_streak(&settings(6, true), 2);
+        assert_eq!(stats.records()[1].best_streak, 3);
+        assert_eq!(stats.records()[0].best_streak, 0);
+    }
+
+    #[test]
+    fn suggest_by_results() {
+        let easy = settings(6, true);
+        let mut stats = Stats::default();
+        for _ in 0..MIN_GAMES_FOR_SUGGESTION - 1 {
+            stats.add(&easy, Some(3));
+        }
+        assert_eq!(stats.get(&easy).unwrap().suggestion(&easy), None);
+
+        let record = stats.add(&easy, Some(4));
+        assert_eq!(
+            record.suggestion(&easy).unwrap(),
+            "You win 100% of these games; try 7 colors or duplicates"
+        );
+
+        let hard = settings(9, false);
+        for _ in 0..MIN_GAMES_FOR_SUGGESTION {
+            stats.add(&hard, None);
+        }
+        let record = stats.add(&hard, Some(8));
+        assert_eq!(
+            record.suggestion(&hard).unwrap(),
+            "You win 9% of these games; try 8 colors or no duplicates"
+        );
+
+        let lost = settings(7, true);
+        for _ in 0..MIN_GAMES_FOR_SUGGESTION {
+            stats.add(&lost, None);
+        }
+        assert_eq!(
+            stats.get(&lost).unwrap().suggestion(&lost).unwrap(),
+            "You win 0% of these games; try 6 colors"
+        );
+
+        // every color is in the code, so one fewer is impossible
+        let mut full = settings(4, true);
+        full.hard = true;
+        full.time_limit = NonZeroU64::new(60);
+        for _ in 0..MIN_GAMES_FOR_SUGGESTION {
+            stats.add(&full, None);
+        }
+        assert_eq!(
+            stats.get(&full).unwrap().suggestion(&full).unwrap(),
+            "You win 0% of these games; try no time limit, no hard mode or 3 holes"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+/// Measures time spent while running
+#[derive(Debug, Default)]
+pub struct Stopwatch {
+    /// Time accumulated before the last start
+    elapsed: Duration,
+    started_at: Option<Instant>,
+}
+
+impl Stopwatch {
+    /// Stopwatch which has already measured `elapsed`
+    pub fn with_elapsed(elapsed: Duration) -> Self {
+        Self {
+            elapsed,
+            started_at: None,
+        }
+    }
+
+    pub fn start(&mut self) {
+        if self.started_at.is_none() {
+            self.started_at = Some(Instant::now());
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(started_at) = self.started_at.take() {
+            self.elapsed += started_at.elapsed();
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.started_at {
+            Some(started_at) => self.elapsed + started_at.elapsed(),
+            None => self.elapsed,
+        }
+    }
+}
+
+/// e.g. "1:05"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopped() {
+        let mut stopwatch = Stopwatch::with_elapsed(Duration::from_secs(5));
+        assert_eq!(stopwatch.elapsed(), Duration::from_secs(5));
+
+        stopwatch.start();
+        stopwatch.stop();
+        let elapsed = stopwatch.elapsed();
+        assert!(elapsed >= Duration::from_secs(5));
+        assert_eq!(stopwatch.elapsed(), elapsed);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "0:05");
+        assert_eq!(format_duration(Duration::from_millis(65_900)), "1:05");
+        assert_eq!(format_duration(Duration::from_secs(600)), "10:00");
+    }
+}
@@ -0,0 +1,59 @@
+//! Suspending to the shell. Raw mode keeps Ctrl+Z from reaching the
+//! terminal driver, so the game stops itself after giving the terminal
+//! back, and takes it again when the shell resumes it.
+
+use anyhow::Result;
+use crossbeam_channel::Receiver;
+
+/// Job control signal delivered to the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// The process was asked to stop from outside, e.g. by `kill -TSTP`
+    Suspend,
+    /// The process was resumed, e.g. by `fg`
+    Resume,
+}
+
+/// Whether the platform has job control
+pub const IS_SUPPORTED: bool = cfg!(unix);
+
+/// Catches the job control signals, which would otherwise stop the process
+/// with the terminal in raw mode
+#[cfg(unix)]
+pub fn signals() -> Result<Receiver<Signal>> {
+    use signal_hook::iterator::Signals;
+
+    let signals = Signals::new([libc::SIGTSTP, libc::SIGCONT])?;
+    let (tx, rx) = crossbeam_channel::unbounded();
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            let signal = if signal == libc::SIGCONT {
+                Signal::Resume
+            } else {
+                Signal::Suspend
+            };
+            if tx.send(signal).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+#[cfg(not(unix))]
+pub fn signals() -> Result<Receiver<Signal>> {
+    Ok(crossbeam_channel::never())
+}
+
+/// Stops the process until it is resumed. SIGSTOP is used since SIGTSTP
+/// is caught by `signals`.
+#[cfg(unix)]
+pub fn stop() {
+    // SAFETY: raise has no preconditions
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn stop() {}
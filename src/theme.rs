@@ -0,0 +1,291 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tui::style::{Color, Modifier, Style};
+
+/// Names of the built-in themes
+pub static BUILTIN_THEMES: &[&str] = &["default", "bright", "light"];
+
+/// Colors and styles used to draw the game
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Colors of code pegs
+    pub pegs: Vec<Color>,
+    /// Color of key pegs for correct color, correct position
+    pub bull: Color,
+    /// Color of key pegs for correct color, wrong position
+    pub cow: Color,
+    /// Style of empty holes
+    pub empty: Style,
+    /// Style of messages
+    pub text: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            pegs: vec![
+                Color::Blue,
+                Color::Red,
+                Color::Green,
+                Color::Yellow,
+                Color::Magenta,
+                Color::White,
+                Color::Cyan,
+            ],
+            bull: Color::Red,
+            cow: Color::White,
+            empty: Style::default(),
+            text: Style::default(),
+        }
+    }
+}
+
+impl Theme {
+    fn builtin(name: &str) -> Option<Self> {
+        let theme = match name {
+            "default" => Self::default(),
+            "bright" => Self {
+                pegs: vec![
+                    Color::LightBlue,
+                    Color::LightRed,
+                    Color::LightGreen,
+                    Color::LightYellow,
+                    Color::LightMagenta,
+                    Color::White,
+                    Color::LightCyan,
+                ],
+                bull: Color::LightRed,
+                ..Self::default()
+            },
+            // for terminals with light background
+            "light" => Self {
+                pegs: vec![
+                    Color::Blue,
+                    Color::Red,
+                    Color::Green,
+                    Color::Yellow,
+                    Color::Magenta,
+                    Color::Black,
+                    Color::Cyan,
+                ],
+                cow: Color::Black,
+                empty: Style::default().fg(Color::DarkGray),
+                ..Self::default()
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Looks up a theme by name, preferring the user-defined ones
+    pub fn resolve(name: &str, user_themes: &BTreeMap<String, ThemeConfig>) -> Result<Self> {
+        ThemeConfig::named(name, user_themes)?
+            .build()
+            .map_err(|err| anyhow::anyhow!("invalid theme \"{}\": {}", name, err))
+    }
+
+    /// Human-readable name of the color of a code peg
+    pub fn color_name(&self, color: usize) -> String {
+        let name = match self.pegs[color] {
+            Color::Black => "black",
+            Color::Red | Color::LightRed => "red",
+            Color::Green | Color::LightGreen => "green",
+            Color::Yellow | Color::LightYellow => "yellow",
+            Color::Blue | Color::LightBlue => "blue",
+            Color::Magenta | Color::LightMagenta => "magenta",
+            Color::Cyan | Color::LightCyan => "cyan",
+            Color::Gray | Color::DarkGray => "gray",
+            Color::White => "white",
+            _ => return format!("color {}", color + 1),
+        };
+        name.to_string()
+    }
+}
+
+/// User-defined theme in the config file, or a theme carried by a rule set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// Built-in theme to take unspecified colors from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pegs: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bull: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    empty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+impl ThemeConfig {
+    /// Definition of the theme with the name. A built-in theme is defined
+    /// by its base alone.
+    pub fn named(name: &str, user_themes: &BTreeMap<String, Self>) -> Result<Self> {
+        if let Some(config) = user_themes.get(name) {
+            return Ok(config.clone());
+        }
+        if Theme::builtin(name).is_some() {
+            return Ok(Self {
+                base: Some(name.to_owned()),
+                ..Self::default()
+            });
+        }
+
+        let names: Vec<_> = BUILTIN_THEMES
+            .iter()
+            .copied()
+            .chain(user_themes.keys().map(String::as_str))
+            .collect();
+        Err(anyhow::anyhow!(
+            "unknown theme \"{}\" (available: {})",
+            name,
+            names.join(", ")
+        ))
+    }
+
+    pub fn build(&self) -> Result<Theme> {
+        let base = self.base.as_deref().unwrap_or("default");
+        let mut theme = Theme::builtin(base)
+            .ok_or_else(|| anyhow::anyhow!("unknown built-in theme \"{}\"", base))?;
+
+        if let Some(pegs) = &self.pegs {
+            if pegs.is_empty() {
+                return Err(anyhow::anyhow!("pegs must not be empty"));
+            }
+            theme.pegs = pegs
+                .iter()
+                .map(|color| parse_color(color))
+                .collect::<Result<_>>()?;
+        }
+        if let Some(bull) = &self.bull {
+            theme.bull = parse_color(bull)?;
+        }
+        if let Some(cow) = &self.cow {
+            theme.cow = parse_color(cow)?;
+        }
+        if let Some(empty) = &self.empty {
+            theme.empty = parse_style(empty)?;
+        }
+        if let Some(text) = &self.text {
+            theme.text = parse_style(text)?;
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Parses a color name (e.g. "red", "light-blue"), a hex code (e.g.
+/// "#ff8800"), or an index of the 256-color palette
+fn parse_color(s: &str) -> Result<Color> {
+    let color = match s.to_lowercase().replace('_', "-").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark-gray" | "dark-grey" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "white" => Color::White,
+        s => {
+            if let Some(hex) = s.strip_prefix('#') {
+                match u32::from_str_radix(hex, 16) {
+                    Ok(rgb) if hex.len() == 6 => {
+                        Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+                    }
+                    _ => return Err(anyhow::anyhow!("invalid color \"{}\"", s)),
+                }
+            } else if let Ok(index) = s.parse() {
+                Color::Indexed(index)
+            } else {
+                return Err(anyhow::anyhow!("invalid color \"{}\"", s));
+            }
+        }
+    };
+    Ok(color)
+}
+
+/// Parses space-separated modifiers and a color (e.g. "bold yellow")
+fn parse_style(s: &str) -> Result<Style> {
+    let mut style = Style::default();
+    for word in s.split_whitespace() {
+        let modifier = match word.to_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            _ => {
+                style = style.fg(parse_color(word)?);
+                continue;
+            }
+        };
+        style = style.modifier(style.modifier | modifier);
+    }
+    Ok(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes() {
+        for name in BUILTIN_THEMES {
+            assert!(Theme::resolve(name, &BTreeMap::new()).is_ok());
+        }
+        assert!(Theme::resolve("unknown", &BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn user_theme() {
+        let config: BTreeMap<String, ThemeConfig> = toml::from_str(
+            r##"
+            [mine]
+            base = "bright"
+            pegs = ["red", "#ff8800", "208"]
+            text = "bold light-cyan"
+            "##,
+        )
+        .unwrap();
+        let theme = Theme::resolve("mine", &config).unwrap();
+
+        assert_eq!(
+            theme.pegs,
+            vec![
+                Color::Red,
+                Color::Rgb(0xff, 0x88, 0x00),
+                Color::Indexed(208)
+            ]
+        );
+        assert_eq!(theme.bull, Color::LightRed);
+        assert_eq!(
+            theme.text,
+            Style::default()
+                .fg(Color::LightCyan)
+                .modifier(Modifier::BOLD)
+        );
+        assert_eq!(theme.color_name(0), "red");
+        assert_eq!(theme.color_name(1), "color 2");
+    }
+
+    #[test]
+    fn invalid_colors() {
+        assert!(parse_color("#ff88").is_err());
+        assert!(parse_color("256").is_err());
+        assert!(parse_color("purple").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use crossterm::terminal::SetTitle;
+use std::io::Write;
+
+/// Saves the window title on xterm-compatible terminals
+const PUSH_TITLE: &str = "\x1b[22;0t";
+/// Restores the window title saved with `PUSH_TITLE`
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Shows the progress of the game in the window title, and in the taskbar
+/// on terminals which understand the ConEmu-style OSC 9;4 sequence
+pub struct TerminalTitle {
+    progress: bool,
+    /// Title and progress written last, to avoid writing them on every frame
+    last: Option<(String, Option<u8>)>,
+}
+
+impl TerminalTitle {
+    pub fn new() -> Self {
+        Self {
+            progress: supports_progress(),
+            last: None,
+        }
+    }
+
+    pub fn enter(&mut self, out: &mut impl Write) -> Result<()> {
+        if cfg!(not(windows)) {
+            out.write_all(PUSH_TITLE.as_bytes())?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// `progress` is a percentage, or `None` to hide the progress
+    pub fn update(
+        &mut self,
+        out: &mut impl Write,
+        title: &str,
+        progress: Option<u8>,
+    ) -> Result<()> {
+        let state = (title.to_owned(), progress);
+        if self.last.as_ref() == Some(&state) {
+            return Ok(());
+        }
+
+        crossterm::queue!(out, SetTitle(title))?;
+        if self.progress {
+            write_progress(out, progress)?;
+        }
+        out.flush()?;
+
+        self.last = Some(state);
+        Ok(())
+    }
+
+    /// Restores the title and clears the progress
+    pub fn leave(&mut self, out: &mut impl Write) -> Result<()> {
+        if self.progress {
+            write_progress(out, None)?;
+        }
+        if cfg!(not(windows)) {
+            out.write_all(POP_TITLE.as_bytes())?;
+        }
+        out.flush()?;
+
+        self.last = None;
+        Ok(())
+    }
+}
+
+fn write_progress(out: &mut impl Write, progress: Option<u8>) -> Result<()> {
+    match progress {
+        Some(percent) => write!(out, "\x1b]9;4;1;{}\x07", percent.min(100))?,
+        None => write!(out, "\x1b]9;4;0;0\x07")?,
+    }
+    Ok(())
+}
+
+/// Terminals known to show the progress instead of printing the sequence
+fn supports_progress() -> bool {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    var("ConEmuANSI") == "ON"
+        || !var("WT_SESSION").is_empty()
+        || matches!(var("TERM_PROGRAM").as_str(), "WezTerm" | "ghostty")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_only_changes() {
+        let mut title = TerminalTitle {
+            progress: true,
+            last: None,
+        };
+        let mut out = Vec::new();
+
+        title.update(&mut out, "a", Some(50)).unwrap();
+        let len = out.len();
+        assert!(String::from_utf8_lossy(&out).contains("\x1b]9;4;1;50\x07"));
+
+        title.update(&mut out, "a", Some(50)).unwrap();
+        assert_eq!(out.len(), len);
+
+        title.update(&mut out, "a", Some(75)).unwrap();
+        assert!(out.len() > len);
+
+        out.clear();
+        title.leave(&mut out).unwrap();
+        assert!(String::from_utf8_lossy(&out).contains("\x1b]9;4;0;0\x07"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Test vectors of the rules, for checking the hints of other
+//! implementations and bots against this one.
+//!
+//! The output is plain text. Lines starting with `#` are comments, the
+//! first of which describes the variant as `colors=6 holes=4
+//! no-duplicate=false`. Every other line is a vector `guess solution bulls
+//! cows`, where codes are written as digits of colors numbered from 1, e.g.
+//! `1123 3124 2 1`. With --digits, the digits are written as they are, and
+//! with --allow-empty, a blank is written as 0.
+
+use crate::{calc_hint, generate_solution, solver, Guess, Settings};
+use anyhow::Result;
+use rand::prelude::*;
+use std::io::{self, Write};
+
+/// Upper bound of the number of vectors written without sampling
+const MAX_EXHAUSTIVE_VECTORS: usize = 10_000_000;
+
+/// Writes the hints of every pair of codes, or of `sample` random pairs
+/// drawn with `seed`
+pub fn export(
+    settings: &Settings,
+    sample: Option<usize>,
+    seed: u64,
+    out: impl Write,
+) -> Result<()> {
+    match write_vectors(settings, sample, seed, out) {
+        // the reader is done, e.g. piped into head
+        Err(err)
+            if err.downcast_ref::<io::Error>().map(io::Error::kind)
+                == Some(io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn write_vectors(
+    settings: &Settings,
+    sample: Option<usize>,
+    seed: u64,
+    mut out: impl Write,
+) -> Result<()> {
+    write!(
+        out,
+        "# colors={} holes={} no-duplicate={}",
+        settings.colors, settings.holes, settings.no_duplicate
+    )?;
+    if settings.allow_empty {
+        write!(out, " allow-empty=true")?;
+    }
+    writeln!(out)?;
+    writeln!(out, "# guess solution bulls cows")?;
+
+    match sample {
+        Some(sample) => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            for _ in 0..sample {
+                let guess = generate_solution(settings, rng.gen());
+                let solution = generate_solution(settings, rng.gen());
+                write_vector(&mut out, settings, &guess, &solution)?;
+            }
+        }
+        None => {
+            let count = solver::code_count(settings)
+                .and_then(|count| count.checked_mul(count))
+                .filter(|&count| count <= MAX_EXHAUSTIVE_VECTORS);
+            if count.is_none() {
+                return Err(anyhow::anyhow!(
+                    "there are too many pairs of codes; use --sample"
+                ));
+            }
+
+            let codes = solver::all_codes(settings);
+            for guess in &codes {
+                for solution in &codes {
+                    write_vector(&mut out, settings, guess, solution)?;
+                }
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn write_vector(
+    out: &mut impl Write,
+    settings: &Settings,
+    guess: &Guess,
+    solution: &Guess,
+) -> Result<()> {
+    let hint = calc_hint(guess, solution, settings.code_colors());
+    writeln!(
+        out,
+        "{} {} {} {}",
+        digits(settings, guess),
+        digits(settings, solution),
+        hint.bulls,
+        hint.cows
+    )?;
+    Ok(())
+}
+
+fn digits(settings: &Settings, code: &Guess) -> String {
+    code.0
+        .iter()
+        .map(|&color| settings.color_number(color).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    fn export_lines(settings: &Settings, sample: Option<usize>, seed: u64) -> Vec<String> {
+        let mut out = Vec::new();
+        export(settings, sample, seed, &mut out).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn exhaustive() {
+        let settings = Settings {
+            colors: NonZeroUsize::new(3).unwrap(),
+            holes: NonZeroUsize::new(2).unwrap(),
+            ..Default::default()
+        };
+        let lines = export_lines(&settings, None, 0);
+        assert_eq!(lines[0], "# colors=3 holes=2 no-duplicate=false");
+        assert_eq!(lines.len(), 2 + 9 * 9);
+        assert!(lines.contains(&"12 21 0 2".to_owned()));
+        assert!(lines.contains(&"33 31 1 0".to_owned()));
+    }
+
+    #[test]
+    fn sampled() {
+        let settings = Settings {
+            colors: NonZeroUsize::new(9).unwrap(),
+            holes: NonZeroUsize::new(9).unwrap(),
+            no_duplicate: true,
+            ..Default::default()
+        };
+        let lines = export_lines(&settings, Some(5), 42);
+        assert_eq!(lines.len(), 2 + 5);
+        assert_eq!(lines, export_lines(&settings, Some(5), 42));
+
+        let mut out = Vec::new();
+        assert!(export(&settings, None, 0, &mut out).is_err());
+    }
+}
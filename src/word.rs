@@ -0,0 +1,378 @@
+//! Word variant, where the code is a word from a word list and each letter
+//! of a guess is marked as in Wordle
+
+use crate::notification::Notifications;
+use crate::{cleanup_terminal, setup_terminal, spawn_input_thread, Backend};
+use anyhow::{Context, Result};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use itertools::Itertools;
+use rand::prelude::*;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+use std::iter;
+use std::path::Path;
+use std::time::Instant;
+use tui::layout::{Constraint, Layout, Rect};
+use tui::style::{Color, Style};
+use tui::widgets::{Paragraph, Text};
+use tui::Frame;
+
+/// Word list used unless another one is given
+pub const DEFAULT_WORDLIST: &str = "/usr/share/dict/words";
+
+/// What a letter of a guess tells about the word. Ordered from the least to
+/// the most informative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mark {
+    /// Not in the word, or not as many times as guessed
+    Absent,
+    /// In the word, but elsewhere
+    Present,
+    /// In the word at this position
+    Correct,
+}
+
+impl Mark {
+    fn style(self) -> Style {
+        let background = match self {
+            Self::Absent => Color::DarkGray,
+            Self::Present => Color::Yellow,
+            Self::Correct => Color::Green,
+        };
+        Style::default().fg(Color::Black).bg(background)
+    }
+}
+
+/// Marks each letter of the guess. A letter guessed more times than it
+/// appears in the word is marked present only as many times as it appears,
+/// counting the correct ones first.
+pub fn mark(guess: &[u8], word: &[u8]) -> Vec<Mark> {
+    let mut marks = vec![Mark::Absent; guess.len()];
+    let mut unmatched = [0usize; 26];
+    for (i, (g, w)) in guess.iter().zip(word).enumerate() {
+        if g == w {
+            marks[i] = Mark::Correct;
+        } else {
+            unmatched[letter_index(*w)] += 1;
+        }
+    }
+
+    for (i, g) in guess.iter().enumerate() {
+        let count = &mut unmatched[letter_index(*g)];
+        if marks[i] != Mark::Correct && *count > 0 {
+            marks[i] = Mark::Present;
+            *count -= 1;
+        }
+    }
+
+    marks
+}
+
+fn letter_index(letter: u8) -> usize {
+    (letter - b'a') as usize
+}
+
+/// Words of `length` letters in the file, one word per line. Words with
+/// other than ASCII letters, such as proper nouns with accents, are skipped.
+pub fn load(path: &Path, length: usize) -> Result<Vec<Vec<u8>>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let words: BTreeSet<_> = content
+        .lines()
+        .map(str::trim)
+        .filter(|word| word.len() == length && word.bytes().all(|c| c.is_ascii_alphabetic()))
+        .map(|word| word.to_ascii_lowercase().into_bytes())
+        .collect();
+
+    if words.is_empty() {
+        return Err(anyhow::anyhow!(
+            "there are no words of {} letters in {}",
+            length,
+            path.display()
+        ));
+    }
+    Ok(words.into_iter().collect())
+}
+
+struct WordGame {
+    /// Sorted to look up guesses
+    words: Vec<Vec<u8>>,
+    word: Vec<u8>,
+    max_guesses: usize,
+    guesses: Vec<Vec<u8>>,
+    marks: Vec<Vec<Mark>>,
+    current_guess: Vec<u8>,
+    /// Glyph of a letter not typed yet
+    empty: &'static str,
+    notifications: Notifications,
+}
+
+impl WordGame {
+    fn length(&self) -> usize {
+        self.word.len()
+    }
+
+    fn is_won(&self) -> bool {
+        self.guesses.last() == Some(&self.word)
+    }
+
+    fn is_over(&self) -> bool {
+        self.is_won() || self.guesses.len() >= self.max_guesses
+    }
+
+    fn on_char(&mut self, c: char) {
+        if !c.is_ascii_alphabetic() {
+            self.notifications
+                .push(format!("Unknown key '{}'; type letters to guess a word", c));
+            return;
+        }
+        if self.current_guess.len() >= self.length() {
+            self.notifications
+                .push("The row is full; press enter to make a guess");
+            return;
+        }
+        self.current_guess.push(c.to_ascii_lowercase() as u8);
+    }
+
+    fn on_backspace(&mut self) {
+        if self.current_guess.pop().is_none() {
+            self.notifications.push("Nothing to undo");
+        }
+    }
+
+    fn on_enter(&mut self) {
+        if self.current_guess.len() < self.length() {
+            self.notifications.push(format!(
+                "Expected {} letters, got {}",
+                self.length(),
+                self.current_guess.len()
+            ));
+            return;
+        }
+        if self.words.binary_search(&self.current_guess).is_err() {
+            self.notifications.push(format!(
+                "\"{}\" is not in the word list",
+                String::from_utf8_lossy(&self.current_guess)
+            ));
+            return;
+        }
+
+        let guess = std::mem::take(&mut self.current_guess);
+        self.marks.push(mark(&guess, &self.word));
+        self.guesses.push(guess);
+    }
+
+    /// Most informative mark of each letter so far
+    fn letter_marks(&self) -> [Option<Mark>; 26] {
+        let mut letters = [None; 26];
+        for (guess, marks) in self.guesses.iter().zip(&self.marks) {
+            for (letter, mark) in guess.iter().zip(marks) {
+                let best = &mut letters[letter_index(*letter)];
+                *best = (*best).max(Some(*mark));
+            }
+        }
+        letters
+    }
+
+    fn height(&self) -> u16 {
+        // legend, margin, board, margin, letters, margin, and messages
+        (1 + 1 + self.max_guesses + 1 + 1 + 1 + 1 + crate::notification::MAX_VISIBLE) as u16
+    }
+
+    fn draw(&self, f: &mut Frame<Backend>) {
+        let size = f.size();
+        if size.height < self.height() {
+            let text = [Text::raw(format!(
+                "The terminal is too small: {} lines are needed, but it has {}",
+                self.height(),
+                size.height
+            ))];
+            f.render_widget(Paragraph::new(text.iter()).wrap(true), size);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(self.max_guesses as u16),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(1),
+            ])
+            .split(size);
+
+        let text = [
+            Text::styled(" A ", Mark::Correct.style()),
+            Text::raw(" Correct position  "),
+            Text::styled(" A ", Mark::Present.style()),
+            Text::raw(" Wrong position  "),
+            Text::styled(" A ", Mark::Absent.style()),
+            Text::raw(" Not in the word"),
+        ];
+        f.render_widget(Paragraph::new(text.iter()), chunks[0]);
+
+        self.draw_board(f, chunks[2]);
+
+        let letter_marks = self.letter_marks();
+        let text: Vec<_> = Itertools::intersperse(
+            (b'a'..=b'z')
+                .zip(letter_marks.iter())
+                .map(|(letter, mark)| {
+                    let letter = (letter as char).to_ascii_uppercase().to_string();
+                    match mark {
+                        Some(mark) => Text::styled(letter, mark.style()),
+                        None => Text::raw(letter),
+                    }
+                }),
+            Text::raw(" "),
+        )
+        .collect();
+        f.render_widget(Paragraph::new(text.iter()), chunks[4]);
+
+        let message = if self.is_won() {
+            format!("You won! ({} guesses)", self.guesses.len())
+        } else if self.is_over() {
+            format!(
+                "You lost. The word was {}",
+                String::from_utf8_lossy(&self.word).to_uppercase()
+            )
+        } else if self.current_guess.len() < self.length() {
+            "Type letters to guess a word".to_owned()
+        } else {
+            "Press enter to make a guess".to_owned()
+        };
+        let text = [Text::raw(message)];
+        f.render_widget(Paragraph::new(text.iter()), chunks[6]);
+
+        let text: Vec<_> = self
+            .notifications
+            .visible()
+            .map(|message| Text::raw(format!("{}\n", message)))
+            .collect();
+        f.render_widget(Paragraph::new(text.iter()), chunks[7]);
+    }
+
+    /// Rows of guesses from the bottom up, as on the board of colors
+    fn draw_board(&self, f: &mut Frame<Backend>, area: Rect) {
+        let empty = Vec::new();
+        let rows = self
+            .guesses
+            .iter()
+            .zip(self.marks.iter().map(Some))
+            .chain(iter::once((&self.current_guess, None)))
+            .chain(iter::repeat((&empty, None)))
+            .take(self.max_guesses.min(area.height as usize));
+
+        for (i, (guess, marks)) in rows.enumerate() {
+            let y = area.y + area.height - 1 - i as u16;
+            let text: Vec<_> = (0..self.length())
+                .map(|j| {
+                    let letter = guess
+                        .get(j)
+                        .map(|letter| (*letter as char).to_ascii_uppercase().to_string())
+                        .unwrap_or_else(|| self.empty.to_owned());
+                    let text = format!(" {} ", letter);
+                    match marks {
+                        Some(marks) => Text::styled(text, marks[j].style()),
+                        None => Text::raw(text),
+                    }
+                })
+                .collect();
+            let row = Rect::new(area.x + 2, y, area.width.saturating_sub(2), 1);
+            f.render_widget(Paragraph::new(text.iter()), row);
+        }
+    }
+}
+
+/// Plays a game with a random word from `words` until it is over or the
+/// player quits. Letters not typed yet are drawn as `empty`.
+pub fn play(words: Vec<Vec<u8>>, max_guesses: usize, empty: &'static str) -> Result<()> {
+    let word = words
+        .choose(&mut rand::thread_rng())
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("the word list is empty"))?;
+    let mut game = WordGame {
+        words,
+        word,
+        max_guesses,
+        guesses: Vec::new(),
+        marks: Vec::new(),
+        current_guess: Vec::new(),
+        empty,
+        notifications: Notifications::default(),
+    };
+
+    let rx = spawn_input_thread();
+    let mut terminal = setup_terminal()?;
+    while !game.is_over() {
+        let timeout = match game.notifications.update(Instant::now()) {
+            Some(duration) => crossbeam_channel::after(duration),
+            None => crossbeam_channel::never(),
+        };
+
+        terminal.draw(|mut f| game.draw(&mut f))?;
+
+        let event = crossbeam_channel::select! {
+            recv(rx) -> event => Some(event?),
+            recv(timeout) -> _ => None,
+        };
+        if let Some(Event::Key(key)) = event {
+            // letters are guesses, so q does not quit
+            match (key.modifiers, key.code) {
+                (_, KeyCode::Esc) | (KeyModifiers::CONTROL, KeyCode::Char('c')) => break,
+                (_, KeyCode::Backspace) => game.on_backspace(),
+                (_, KeyCode::Enter) => game.on_enter(),
+                (_, KeyCode::Char(c)) => game.on_char(c),
+                _ => (),
+            }
+        }
+    }
+
+    game.notifications.update(Instant::now());
+    terminal.draw(|mut f| game.draw(&mut f))?;
+    let mut stderr = io::stderr();
+    crossterm::queue!(stderr, crossterm::cursor::MoveTo(0, game.height()))?;
+    stderr.flush()?;
+    cleanup_terminal(&mut terminal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Mark::*;
+
+    #[test]
+    fn mark_letters() {
+        assert_eq!(mark(b"crane", b"crane"), [Correct; 5]);
+        assert_eq!(
+            mark(b"cigar", b"rebut"),
+            [Absent, Absent, Absent, Absent, Present]
+        );
+        // only one of the e's is in the word besides the correct one
+        assert_eq!(
+            mark(b"eerie", b"there"),
+            [Present, Absent, Present, Absent, Correct]
+        );
+        assert_eq!(
+            mark(b"speed", b"abide"),
+            [Absent, Absent, Present, Absent, Present]
+        );
+    }
+
+    #[test]
+    fn load_words() {
+        let path = std::env::temp_dir().join("codebreaker-words.txt");
+        fs::write(&path, "Crane\nslate\nfour\nnaïve\ncrane\n\nabcde\n").unwrap();
+        let words = load(&path, 5).unwrap();
+        assert_eq!(
+            words,
+            [b"abcde".to_vec(), b"crane".to_vec(), b"slate".to_vec()]
+        );
+        assert!(load(&path, 7).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}